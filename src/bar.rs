@@ -0,0 +1,214 @@
+// output backends for status bars, all built on the one line conditions
+use chrono::Timelike;
+use serde::Serialize;
+use std::fmt::Write;
+
+use crate::color::get_temp_rgb;
+use crate::forecast::Forecast;
+use crate::render::{current_conditions, get_time_index, to_am_pm, wind_di_decode, Conditions};
+use crate::template::Style;
+use crate::wmo;
+use crate::RenderOptions;
+
+// hours of forecast in the waybar tooltip
+const TOOLTIP_HOURS: usize = 12;
+
+#[derive(Serialize)]
+struct Waybar {
+    text: String,
+    tooltip: String,
+    class: &'static str,
+    percentage: i32,
+}
+
+// css class for the current weather, lets bars style e.g. storms differently
+fn class(wmo: u8) -> &'static str {
+    match wmo {
+        0 | 1 => "clear",
+        2 | 3 => "cloudy",
+        40..=49 => "fog",
+        50..=59 => "drizzle",
+        60..=69 | 80..=84 => "rain",
+        70..=79 | 85..=86 => "snow",
+        95..=99 => "storm",
+        _ => "unknown",
+    }
+}
+
+// the status text without the padding wmo_decode adds for tables
+fn text(c: &Conditions, opts: &RenderOptions) -> String {
+    match &opts.template {
+        Some(template) => template.render(c, Style::Plain),
+        None => c.line(opts.vs16).trim_end().to_string(),
+    }
+}
+
+// condensed version of the hourly table, one row per hour without bars or colors
+fn tooltip(md: &Forecast, opts: &RenderOptions, now: u64) -> String {
+    let scale = opts.temperature.scale;
+    let m = &md.minutely_15;
+    let now = get_time_index(&m.time, now);
+    let end = (now + TOOLTIP_HOURS * 4).min(m.time.len());
+
+    let mut dst = String::from("TIME  TEMP  HMT PRCP  WIND WMO");
+    for i in (now..end).step_by(4) {
+        let hour = md.local_time(m.time[i]).hour() as i64;
+        write!(
+            dst,
+            "\n{:4.4} {:5.1}{} {:3.0}% {:3.0}% {:>2.0} {:2.2} {}",
+            to_am_pm(hour),
+            scale.from_celsius(m.temperature_2m[i]),
+            scale.symbol(),
+            m.relative_humidity_2m[i],
+            m.precipitation_probability[i],
+            m.wind_speed_10m[i],
+            wind_di_decode(m.wind_direction_10m[i]),
+            wmo::text(m.weather_code[i]),
+        )
+        .unwrap();
+    }
+    dst
+}
+
+// a JSON object for a waybar custom module with `"return-type": "json"`
+pub fn waybar(md: &Forecast, opts: &RenderOptions, now: u64) -> String {
+    let c = current_conditions(md, opts, now);
+    let output = Waybar {
+        text: text(&c, opts),
+        tooltip: tooltip(md, opts, now),
+        class: class(c.wmo),
+        percentage: c.precip_max,
+    };
+    format!("{}\n", serde_json::to_string(&output).unwrap())
+}
+
+// full_text, short_text and color lines as read by i3blocks
+pub fn i3blocks(md: &Forecast, opts: &RenderOptions, now: u64) -> String {
+    let c = current_conditions(md, opts, now);
+    format!(
+        "{}\n{} ~{}%\n{}\n",
+        text(&c, opts),
+        c.temperature.format(c.temp),
+        c.precip_max,
+        get_temp_rgb(c.temp).hex()
+    )
+}
+
+// polybar/lemonbar format tags, temperature and weather colored like the tables
+pub fn polybar(md: &Forecast, opts: &RenderOptions, now: u64) -> String {
+    let c = current_conditions(md, opts, now);
+    if let Some(template) = &opts.template {
+        return format!("{}\n", template.render(&c, Style::Polybar));
+    }
+    let mut dst = format!(
+        "%{{F{}}}{}%{{F-}} {}% {} %{{F{}}}{}%{{F-}} ~{}%",
+        get_temp_rgb(c.temp).hex(),
+        c.temperature.format(c.temp),
+        c.humid,
+        c.wind_format,
+        c.wmo_rgb.hex(),
+        format!("{:.8}", c.wmo_string).trim_end(),
+        c.precip_max,
+    );
+    if let Some(minutes) = c.stale {
+        write!(dst, " ({minutes}m old)").unwrap();
+    }
+    dst.push('\n');
+    dst
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::fixture;
+
+    // 09:07 on the fixture's first day, clear and 11°
+    const NOW: u64 = 1792246020;
+
+    #[test]
+    fn waybar_object() {
+        let md = fixture();
+        let opts = RenderOptions::default();
+        let output = waybar(&md, &opts, NOW);
+        assert!(output.ends_with('\n') && output.lines().count() == 1);
+
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        let object = value.as_object().unwrap();
+        let keys: Vec<&str> = object.keys().map(String::as_str).collect();
+        assert_eq!(keys.len(), 4);
+        for key in ["text", "tooltip", "class", "percentage"] {
+            assert!(keys.contains(&key), "{key}");
+        }
+        let c = current_conditions(&md, &opts, NOW);
+        assert_eq!(value["class"], "clear");
+        assert_eq!(value["percentage"], c.precip_max);
+        assert_eq!(value["text"], text(&c, &opts));
+    }
+
+    #[test]
+    fn tooltip_rows() {
+        let md = fixture();
+        let opts = RenderOptions::default();
+        let tooltip = tooltip(&md, &opts, NOW);
+        let lines: Vec<&str> = tooltip.lines().collect();
+        assert_eq!(lines[0], "TIME  TEMP  HMT PRCP  WIND WMO");
+        assert_eq!(lines.len(), 1 + TOOLTIP_HOURS);
+        assert_eq!(lines[1], "9am   11.0°  68%   0% 10 SW Clear sky");
+        assert!(lines[2].starts_with("10am"), "{}", lines[2]);
+
+        // fewer hours left in the forecast than the tooltip has rows
+        let last = *md.minutely_15.time.last().unwrap() as u64;
+        let tooltip = super::tooltip(&md, &opts, last - 3 * 3600);
+        assert_eq!(tooltip.lines().count(), 1 + 4);
+    }
+
+    #[test]
+    fn classes() {
+        let cases = [
+            (0, "clear"),
+            (1, "clear"),
+            (3, "cloudy"),
+            (44, "fog"),
+            (48, "fog"),
+            (56, "drizzle"),
+            (66, "rain"),
+            (82, "rain"),
+            (77, "snow"),
+            (86, "snow"),
+            (95, "storm"),
+            (99, "storm"),
+            (100, "unknown"),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(class(code), expected, "{code}");
+        }
+    }
+
+    #[test]
+    fn i3blocks_lines() {
+        let md = fixture();
+        let opts = RenderOptions::default();
+        let c = current_conditions(&md, &opts, NOW);
+        let output = i3blocks(&md, &opts, NOW);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], text(&c, &opts));
+        assert_eq!(lines[1], format!("11° ~{}%", c.precip_max));
+        assert_eq!(lines[2], get_temp_rgb(11.0).hex());
+        assert!(lines[2].starts_with('#') && lines[2].len() == 7);
+    }
+
+    #[test]
+    fn polybar_tags() {
+        let md = fixture();
+        let opts = RenderOptions::default();
+        let output = polybar(&md, &opts, NOW);
+        let temp = format!("%{{F{}}}11°%{{F-}} ", get_temp_rgb(11.0).hex());
+        assert!(output.starts_with(&temp), "{output}");
+        assert_eq!(output.matches("%{F-}").count(), 2);
+        assert!(!output.contains("old"));
+        // an old forecast says how old
+        let output = polybar(&md, &opts, NOW + 3600);
+        assert!(output.ends_with(" (67m old)\n"), "{output}");
+    }
+}
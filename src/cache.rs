@@ -0,0 +1,208 @@
+// on disk cache of forecasts, one file per location, provider, unit system and series set
+use anyhow::{anyhow, Result};
+use chrono::DateTime;
+use std::{
+    env,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use crate::forecast::Forecast;
+use crate::{FetchOptions, LatLon, TempScale};
+
+// entries not refreshed within this long are deleted on the next save
+const MAX_AGE: Duration = Duration::from_secs(7 * 24 * 3600);
+// upper bound on the number of locations kept warm at once
+const MAX_ENTRIES: usize = 16;
+
+// $XDG_CACHE_HOME/weather-rs, falling back to ~/.cache and then the temp dir
+pub fn dir() -> PathBuf {
+    let base = match (env::var_os("XDG_CACHE_HOME"), env::var_os("HOME")) {
+        (Some(dir), _) if !dir.is_empty() => PathBuf::from(dir),
+        (_, Some(home)) => PathBuf::from(home).join(".cache"),
+        _ => env::temp_dir(),
+    };
+    base.join("weather-rs")
+}
+
+// rounded to roughly a kilometer so small jitter in the location still hits
+pub fn entry_path(latlon: LatLon, provider: &str, units: &str) -> PathBuf {
+    dir().join(format!(
+        "{:.2}_{:.2}_{provider}_{units}.json",
+        latlon.lat, latlon.lon
+    ))
+}
+
+// the entry at `path` if it is younger than `timeout` and was fetched with the same options
+// for roughly the same location
+pub fn load(
+    path: &Path,
+    opts: &FetchOptions,
+    latlon: Option<LatLon>,
+    timeout: u64,
+    now: u64,
+) -> Result<Forecast> {
+    let Ok(content) = fs::read_to_string(path) else {
+        return Err(anyhow!("Failed to read file: {path:?}"));
+    };
+
+    let Ok(json) = serde_json::from_str::<Forecast>(&content) else {
+        return Err(anyhow!("Failed deserialize file content"));
+    };
+
+    if (now as i64 - json.current.time as i64).unsigned_abs() >= timeout {
+        return Err(anyhow!("Cache outdated."));
+    }
+
+    let provider = opts.provider.backend().name();
+    if json.provider != provider {
+        return Err(anyhow!(
+            "Cache provider did not match configured: {} != {provider}",
+            json.provider,
+        ));
+    }
+
+    let units = &opts.units;
+    let expected = [
+        (&json.units.temperature, TempScale::Celsius.label()),
+        (&json.units.wind_speed, units.wind.label()),
+        (&json.units.precipitation, units.precipitation.label()),
+        (&json.units.pressure, units.pressure.label()),
+    ];
+    for (cached, configured) in expected {
+        if cached != configured {
+            return Err(anyhow!(
+                "Cache unit did not match configured: {cached:?} != {configured:?}"
+            ));
+        }
+    }
+
+    // missing daily stats are made up for from the hourly samples
+    let series = &opts.series;
+    if (series.minutely_15 && json.minutely_15.time.is_empty())
+        || (series.hourly && json.hourly.time.is_empty())
+    {
+        return Err(anyhow!("Cache lacks a series this view needs"));
+    }
+
+    // At their maximum (since longitude varies by latitude) one unit of either corresponds
+    // to 111km on earth. so this has a maximum error of √((111 * n)² * 2) or ~7.8 at 0.02
+    const REQ_ACCURACY: f64 = 0.05;
+    if let Some(latlon) = latlon {
+        if (latlon.lat - json.latitude).abs() > REQ_ACCURACY
+            || (latlon.lon - json.longitude).abs() > REQ_ACCURACY
+        {
+            return Err(anyhow!(
+                "Cache lat or lon did not match desired. {} =! {} OR {} =! {}",
+                latlon.lat,
+                json.latitude,
+                latlon.lon,
+                json.longitude
+            ));
+        }
+    }
+
+    Ok(json)
+}
+
+pub fn save(path: &Path, md: &Forecast) -> Result<()> {
+    fs::create_dir_all(dir())?;
+    fs::write(path, serde_json::to_string(md)?)?;
+    evict()
+}
+
+// (path, time since last write) for every entry, newest first
+fn entries() -> Result<Vec<(PathBuf, Duration)>> {
+    let mut entries = vec![];
+    for entry in fs::read_dir(dir())? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|e| e != "json") {
+            continue;
+        }
+        let modified = fs::metadata(&path)?.modified()?;
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default();
+        entries.push((path, age));
+    }
+    entries.sort_by_key(|(_, age)| *age);
+    Ok(entries)
+}
+
+// the most recently written entry from the given provider and cache key
+pub fn newest(provider: &str, units: &str) -> Option<PathBuf> {
+    let suffix = format!("_{provider}_{units}.json");
+    entries()
+        .ok()?
+        .into_iter()
+        .map(|(path, _)| path)
+        .find(|path| path.to_string_lossy().ends_with(&suffix))
+}
+
+// drops entries older than MAX_AGE and everything past the MAX_ENTRIES newest
+fn evict() -> Result<()> {
+    for (i, (path, age)) in entries()?.into_iter().enumerate() {
+        if i >= MAX_ENTRIES || age > MAX_AGE {
+            fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+fn name(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+// (name, time since last write) for every entry, newest first
+pub fn list() -> Vec<(String, Duration)> {
+    entries()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(path, age)| (name(&path), age))
+        .collect()
+}
+
+// deletes every entry, returning how many there were
+pub fn clear() -> Result<usize> {
+    let entries = entries().unwrap_or_default();
+    for (path, _) in &entries {
+        fs::remove_file(path)?;
+    }
+    Ok(entries.len())
+}
+
+// a summary of one entry, `entry` is a name as shown by `cache list`
+pub fn show(entry: &str) -> Result<String> {
+    let path = dir().join(format!("{entry}.json"));
+    let content =
+        fs::read_to_string(&path).map_err(|e| anyhow!("no cache entry \"{entry}\": {e}"))?;
+    let md: Forecast = serde_json::from_str(&content)?;
+
+    let mut dst = String::new();
+    writeln!(dst, "path:        {}", path.display())?;
+    writeln!(dst, "provider:    {}", md.provider)?;
+    writeln!(dst, "coordinates: {:.4}, {:.4}", md.latitude, md.longitude)?;
+    writeln!(dst, "elevation:   {}m", md.elevation)?;
+    writeln!(
+        dst,
+        "timezone:    {} ({})",
+        md.timezone, md.timezone_abbreviation
+    )?;
+    writeln!(dst, "temperature: {}", md.units.temperature)?;
+    writeln!(dst, "wind speed:  {}", md.units.wind_speed)?;
+    if let Some(fetched) = DateTime::from_timestamp(md.current.time as i64, 0) {
+        writeln!(dst, "fetched at:  {}", fetched.format("%Y-%m-%d %H:%M UTC"))?;
+    }
+    writeln!(
+        dst,
+        "samples:     {} 15-minutely, {} hourly, {} daily",
+        md.minutely_15.time.len(),
+        md.hourly.time.len(),
+        md.daily.time.len()
+    )?;
+    Ok(dst)
+}
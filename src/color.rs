@@ -0,0 +1,110 @@
+// 24 bit terminal colors and the gradients the tables are drawn with
+use std::fmt::Write;
+
+#[derive(Clone, Debug)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub fn hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+    // the inverse of hex(), "#rrggbb"
+    pub fn from_hex(s: &str) -> Option<Rgb> {
+        let s = s.strip_prefix('#')?;
+        if s.len() != 6 || !s.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&s[i..i + 2], 16).ok();
+        Some(Rgb {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+        })
+    }
+
+    // nothing is written when `enabled` is false, so callers don't need to branch
+    pub fn write_fg_esc(&self, dst: &mut impl Write, enabled: bool) -> std::fmt::Result {
+        if enabled {
+            write!(dst, "\x1b[38;2;{};{};{}m", self.r, self.g, self.b)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_bg_esc(&self, dst: &mut impl Write, enabled: bool) -> std::fmt::Result {
+        if enabled {
+            write!(dst, "\x1b[48;2;{};{};{}m", self.r, self.g, self.b)?;
+        }
+        Ok(())
+    }
+}
+
+// ends whatever colors were written before, nothing when they are off
+pub fn reset(enabled: bool) -> &'static str {
+    match enabled {
+        true => "\x1b[0m",
+        false => "",
+    }
+}
+
+// colors to use with rgb_lerp
+pub const WHITE: Rgb = Rgb { r: 222, g: 222, b: 222 };
+pub const BLACK: Rgb = Rgb { r: 0, g: 0, b: 0 };
+pub const L_GRAY: Rgb = Rgb { r: 180, g: 180, b: 180 };
+pub const RED: Rgb = Rgb { r: 255, g: 0, b: 0 };
+// const ORANGE: Rgb = Rgb { r: 255, g: 128, b: 0 };
+pub const ALT_YELLOW: Rgb = Rgb { r: 235, g: 213, b: 122 };
+pub const YELLOW: Rgb = Rgb { r: 255, g: 233, b: 102 };
+pub const ICE_BLUE: Rgb = Rgb { r: 157, g: 235, b: 255 };
+pub const CLEAR_BLUE: Rgb = Rgb { r: 92, g: 119, b: 242 };
+pub const MID_BLUE: Rgb = Rgb { r: 68, g: 99, b: 240 };
+pub const DEEP_BLUE: Rgb = Rgb { r: 45, g: 80, b: 238 };
+pub const PURPLE: Rgb = Rgb { r: 58, g: 9, b: 66 };
+
+const OG0: Rgb = Rgb { r: 255, g: 255, b: 255 };
+const OG1: Rgb = Rgb { r: 79, g: 185, b: 243 };
+const OG2: Rgb = Rgb { r: 74, g: 137, b: 135 };
+const OG3: Rgb = Rgb { r: 229, g: 219, b: 93 };
+const OG4: Rgb = Rgb { r: 249, g: 203, b: 49 };
+const OG5: Rgb = Rgb { r: 209, g: 68, b: 12 };
+
+// linearly interpolates A's position between B and C to D and E
+pub fn lerp(a: f64, b: f64, c: f64, d: f64, e: f64) -> f64 {
+    (a - b) * (e - d) / (c - b) + d
+}
+
+// same as lerp() but the output values are Rgb structs
+pub fn rgb_lerp(x: f64, y: f64, z: f64, color1: &Rgb, color2: &Rgb) -> Rgb {
+    Rgb {
+        r: lerp(x, y, z, color1.r as f64, color2.r as f64) as u8,
+        g: lerp(x, y, z, color1.g as f64, color2.g as f64) as u8,
+        b: lerp(x, y, z, color1.b as f64, color2.b as f64) as u8,
+    }
+}
+
+// from °C, the stops are 10, 32, 50, 80, 105 and 130 °F
+pub fn get_temp_rgb(temp: f64) -> Rgb {
+    match temp {
+        x if (40.56..54.44).contains(&x) => rgb_lerp(temp, 40.56, 54.44, &OG4, &OG5),
+        x if (26.67..40.56).contains(&x) => rgb_lerp(temp, 26.67, 40.56, &OG3, &OG4),
+        x if (10.0..26.67).contains(&x) => rgb_lerp(temp, 10.0, 26.67, &OG2, &OG3),
+        x if (0.0..10.0).contains(&x) => rgb_lerp(temp, 0.0, 10.0, &OG1, &OG2),
+        x if (-12.22..0.0).contains(&x) => rgb_lerp(temp, -12.22, 0.0, &OG0, &OG1),
+        x if x <= -12.22 => OG0,
+        _ => rgb_lerp(temp, -73.33, 54.44, &BLACK, &WHITE),
+    }
+}
+
+// from °C, white below 70 °F turning red by 95 °F
+pub fn get_wb_rgb(wb: f64) -> Rgb {
+    match wb {
+        x if x > 35.0 => RED,
+        x if (21.11..35.0).contains(&x) => rgb_lerp(wb, 21.11, 35.0, &WHITE, &RED),
+        x if x < 21.11 => WHITE,
+        _ => rgb_lerp(wb, -73.33, 54.44, &BLACK, &WHITE),
+    }
+}
@@ -0,0 +1,413 @@
+// the columns of the hourly table, each one knows its header, width, color and where its
+// value comes from so adding a field is a new variant rather than a change to the row loop
+use chrono::Timelike;
+use serde::Deserialize;
+
+use crate::color::{get_temp_rgb, get_wb_rgb, rgb_lerp, Rgb, DEEP_BLUE, ICE_BLUE, WHITE};
+use crate::forecast::Forecast;
+use crate::moon::Moon;
+use crate::render::{mk_bar, to_am_pm, wind_di_decode, wmo_decode};
+use crate::units::TempDisplay;
+pub use crate::width::Align;
+use crate::wmo;
+use crate::{compute_wet_bulb, RenderOptions};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Column {
+    Time,
+    Temp,
+    TempBar,
+    Feels,
+    #[value(alias = "hmt")]
+    #[serde(alias = "hmt")]
+    Humidity,
+    #[value(alias = "wb")]
+    #[serde(alias = "wb")]
+    WetBulb,
+    Precip,
+    PrecipBar,
+    Rain,
+    #[value(alias = "pres")]
+    #[serde(alias = "pres")]
+    Pressure,
+    Wind,
+    Wmo,
+}
+
+impl Column {
+    pub const DEFAULT: [Column; 9] = [
+        Column::Time,
+        Column::Temp,
+        Column::TempBar,
+        Column::Humidity,
+        Column::WetBulb,
+        Column::Precip,
+        Column::PrecipBar,
+        Column::Wind,
+        Column::Wmo,
+    ];
+}
+
+// bars narrower than this are dropped rather than drawn
+const MIN_BAR: usize = 4;
+const MAX_BAR: usize = 24;
+
+// columns given up, in order, while the table is wider than the terminal
+const DROP_ORDER: [Column; 5] = [
+    Column::PrecipBar,
+    Column::TempBar,
+    Column::WetBulb,
+    Column::Humidity,
+    Column::Feels,
+];
+
+// the columns that fit and the width each one gets
+pub struct Layout {
+    pub columns: Vec<(Column, usize)>,
+    pub bar: usize,
+}
+
+// characters a row takes with the row marker and a space between cells
+fn row_width(widths: impl Iterator<Item = usize>) -> usize {
+    let (n, sum) = widths.fold((0usize, 0), |(n, sum), w| (n + 1, sum + w));
+    2 + sum + n.saturating_sub(1)
+}
+
+// fits `columns` into a terminal `width` characters wide: bars shrink and then go, then the
+// rest of DROP_ORDER, and finally shrinkable columns are cut. never wider than asked unless
+// even the minimal table isn't
+pub fn layout(columns: &[Column], width: usize, temp: &TempDisplay) -> Layout {
+    // the last column would wrap in some terminals
+    let avail = width.saturating_sub(1);
+    let mut columns = columns.to_vec();
+
+    let bar = loop {
+        let bars = columns
+            .iter()
+            .filter(|c| matches!(c.spec().width, Width::Bar))
+            .count();
+        let fixed = row_width(columns.iter().map(|c| match c.spec().width {
+            Width::Fixed(w) | Width::Shrink { max: w, .. } => w,
+            Width::Temp => temp.cell_width(),
+            Width::Bar => 0,
+        }));
+        let room = avail.saturating_sub(fixed);
+        match bars {
+            0 if fixed <= avail => break 0,
+            0 => {}
+            n if (room / n).min(MAX_BAR) >= MIN_BAR => break (room / n).min(MAX_BAR),
+            _ => {}
+        }
+        match DROP_ORDER.iter().find(|c| columns.contains(c)) {
+            Some(drop) => columns.retain(|c| c != drop),
+            None => break 0,
+        }
+    };
+    // bars can't be left in here, every one of them is in DROP_ORDER
+    let mut widths: Vec<(Column, usize)> = columns
+        .iter()
+        .map(|c| match c.spec().width {
+            Width::Fixed(w) | Width::Shrink { max: w, .. } => (*c, w),
+            Width::Temp => (*c, temp.cell_width()),
+            Width::Bar => (*c, bar),
+        })
+        .collect();
+
+    let mut over = row_width(widths.iter().map(|(_, w)| *w)).saturating_sub(avail);
+    for (c, w) in widths.iter_mut() {
+        if let Width::Shrink { min, .. } = c.spec().width {
+            let cut = over.min(w.saturating_sub(min));
+            *w -= cut;
+            over -= cut;
+        }
+    }
+
+    Layout { columns: widths, bar }
+}
+
+// what a cell can see: the forecast, the sample and the table-wide state
+pub struct Cell<'a> {
+    pub md: &'a Forecast,
+    // index into md.minutely_15
+    pub i: usize,
+    pub view: &'a View<'a>,
+}
+
+// state shared by every row of one table
+pub struct View<'a> {
+    pub opts: &'a RenderOptions,
+    pub bar_max: usize,
+    // range the temperature bar spans
+    pub temp_low: f64,
+    pub temp_high: f64,
+    // today's, decides between day and night labels
+    pub sunrise: u32,
+    pub sunset: u32,
+}
+
+pub enum Width {
+    Fixed(usize),
+    // whatever the temperature display needs, wider with a second unit
+    Temp,
+    // shares what is left of the terminal with the other bars
+    Bar,
+    // cut down to as little as `min` before anything else is dropped
+    Shrink { max: usize, min: usize },
+}
+
+pub struct Spec {
+    pub header: &'static str,
+    pub align: Align,
+    // cells are padded or cut to this many characters
+    pub width: Width,
+    pub value: fn(&Cell) -> String,
+    // None keeps the row's default color
+    pub color: fn(&Cell) -> Option<Rgb>,
+}
+
+fn no_color(_: &Cell) -> Option<Rgb> {
+    None
+}
+
+impl Cell<'_> {
+    fn temp(&self) -> f64 {
+        self.md.minutely_15.temperature_2m[self.i]
+    }
+
+    fn humidity(&self) -> f64 {
+        self.md.minutely_15.relative_humidity_2m[self.i]
+    }
+
+    fn precip(&self) -> f64 {
+        self.md.minutely_15.precipitation_probability[self.i]
+    }
+
+    // the amount falling during this sample, None for caches from before it was fetched
+    fn rain(&self) -> Option<f64> {
+        self.md.minutely_15.precipitation.get(self.i).copied()
+    }
+
+    fn rain_mm(&self) -> Option<f64> {
+        let rain = self.rain()?;
+        Some(match self.md.units.precipitation.as_str() {
+            "inch" => rain * 25.4,
+            _ => rain,
+        })
+    }
+
+    fn pressure(&self) -> Option<f64> {
+        self.md.pressure_at(self.md.minutely_15.time[self.i])
+    }
+
+    fn feels(&self) -> Option<f64> {
+        self.md
+            .minutely_15
+            .apparent_temperature
+            .get(self.i)
+            .copied()
+    }
+
+    fn wet_bulb(&self) -> f64 {
+        compute_wet_bulb(self.temp(), self.humidity())
+    }
+
+    fn temperature(&self) -> &TempDisplay {
+        &self.view.opts.temperature
+    }
+
+    fn wmo(&self) -> (String, &'static Rgb) {
+        let time = self.md.minutely_15.time[self.i];
+        wmo_decode(
+            self.md.minutely_15.weather_code[self.i],
+            time < self.view.sunset && time > self.view.sunrise,
+            &Moon::at(time),
+            self.view.opts.emoji,
+        )
+    }
+}
+
+impl Column {
+    pub fn spec(&self) -> Spec {
+        match self {
+            Column::Time => Spec {
+                header: "TIME",
+                align: Align::Left,
+                width: Width::Fixed(4),
+                value: |c| {
+                    let hour = c.md.local_time(c.md.minutely_15.time[c.i]).hour();
+                    to_am_pm(hour as i64)
+                },
+                color: no_color,
+            },
+            Column::Temp => Spec {
+                header: "TEMP",
+                align: Align::Right,
+                width: Width::Temp,
+                value: |c| c.temperature().cell(c.temp()),
+                color: |c| Some(get_temp_rgb(c.temp())),
+            },
+            Column::TempBar => Spec {
+                header: "TEMP-BAR",
+                align: Align::Left,
+                width: Width::Bar,
+                value: |c| {
+                    let v = c.view;
+                    mk_bar(&c.temp(), &v.temp_low, &v.temp_high, &1.0, v.bar_max)
+                },
+                color: |c| Some(get_temp_rgb(c.temp())),
+            },
+            Column::Feels => Spec {
+                header: "FEEL",
+                align: Align::Right,
+                width: Width::Temp,
+                value: |c| match c.feels() {
+                    Some(feels) => c.temperature().cell(feels),
+                    None => String::new(),
+                },
+                color: |c| Some(get_temp_rgb(c.feels()?)),
+            },
+            Column::Humidity => Spec {
+                header: "HMT",
+                align: Align::Right,
+                width: Width::Fixed(4),
+                value: |c| format!("{:3.0}%", c.humidity()),
+                color: |c| Some(rgb_lerp(c.humidity(), 30.0, 90.0, &WHITE, &DEEP_BLUE)),
+            },
+            Column::WetBulb => Spec {
+                header: "WB",
+                align: Align::Right,
+                width: Width::Fixed(5),
+                value: |c| format!("{:5.1}", c.temperature().convert(c.wet_bulb())),
+                color: |c| Some(get_wb_rgb(c.wet_bulb())),
+            },
+            Column::Precip => Spec {
+                header: "PRCP",
+                align: Align::Right,
+                width: Width::Fixed(4),
+                value: |c| format!("{:3.0}%", c.precip()),
+                color: |c| Some(rgb_lerp(c.precip(), 0.0, 100.0, &ICE_BLUE, &DEEP_BLUE)),
+            },
+            Column::PrecipBar => Spec {
+                header: "PRCP-BAR",
+                align: Align::Left,
+                width: Width::Bar,
+                value: |c| mk_bar(&c.precip(), &0.0, &100.0, &0.0, c.view.bar_max),
+                color: |c| Some(rgb_lerp(c.precip(), 0.0, 100.0, &ICE_BLUE, &DEEP_BLUE)),
+            },
+            Column::Rain => Spec {
+                header: "RAIN",
+                align: Align::Right,
+                width: Width::Fixed(5),
+                value: |c| match (c.rain(), c.md.units.precipitation.as_str()) {
+                    (Some(rain), "inch") => format!("{rain:5.2}"),
+                    (Some(rain), _) => format!("{rain:5.1}"),
+                    (None, _) => String::new(),
+                },
+                // 4mm in a quarter hour is a downpour
+                color: |c| Some(rgb_lerp(c.rain_mm()?, 0.0, 4.0, &ICE_BLUE, &DEEP_BLUE)),
+            },
+            Column::Pressure => Spec {
+                header: "PRES",
+                align: Align::Right,
+                width: Width::Fixed(6),
+                value: |c| match (c.pressure(), c.md.units.pressure.as_str()) {
+                    (Some(p), "inHg") => format!("{p:6.2}"),
+                    (Some(p), _) => format!("{p:6.1}"),
+                    (None, _) => String::new(),
+                },
+                color: no_color,
+            },
+            Column::Wind => Spec {
+                header: "WIND",
+                align: Align::Right,
+                width: Width::Fixed(5),
+                value: |c| {
+                    let m = &c.md.minutely_15;
+                    let direction = wind_di_decode(m.wind_direction_10m[c.i]);
+                    format!("{:>2.0} {:2.2}", m.wind_speed_10m[c.i], direction)
+                },
+                color: no_color,
+            },
+            Column::Wmo => Spec {
+                header: "WMO",
+                align: Align::Left,
+                width: Width::Shrink {
+                    max: wmo::label_width(),
+                    min: 3,
+                },
+                value: |c| c.wmo().0,
+                color: |c| Some(c.wmo().1.clone()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Column::*;
+
+    fn fit(width: usize) -> Layout {
+        layout(&Column::DEFAULT, width, &TempDisplay::default())
+    }
+
+    #[test]
+    fn layout_at_widths() {
+        let wide = [
+            (Time, 4),
+            (Temp, 6),
+            (TempBar, 24),
+            (Humidity, 4),
+            (WetBulb, 5),
+            (Precip, 4),
+            (PrecipBar, 24),
+            (Wind, 5),
+            (Wmo, 16),
+        ];
+        let l = fit(120);
+        assert_eq!(l.columns, wide);
+        assert_eq!(l.bar, MAX_BAR);
+
+        // the bars shrink first
+        let l = fit(80);
+        assert_eq!(l.bar, 12);
+        assert_eq!(l.columns[2], (TempBar, 12));
+        assert_eq!(l.columns[6], (PrecipBar, 12));
+
+        // then the precipitation bar goes
+        let l = fit(60);
+        assert_eq!(
+            l.columns,
+            [
+                (Time, 4),
+                (Temp, 6),
+                (TempBar, 6),
+                (Humidity, 4),
+                (WetBulb, 5),
+                (Precip, 4),
+                (Wind, 5),
+                (Wmo, 16)
+            ]
+        );
+
+        // then the other bar, wet bulb and humidity, and the weather text is cut
+        let l = fit(40);
+        assert_eq!(l.bar, 0);
+        assert_eq!(
+            l.columns,
+            [(Time, 4), (Temp, 6), (Precip, 4), (Wind, 5), (Wmo, 14)]
+        );
+    }
+
+    #[test]
+    fn layout_fits() {
+        for width in 30..=160 {
+            let l = fit(width);
+            let used = row_width(l.columns.iter().map(|(_, w)| *w));
+            assert!(used < width, "{used} columns at width {width}");
+        }
+        // narrower than the minimal table, it overflows rather than losing more
+        assert_eq!(fit(10).columns.len(), 5);
+    }
+}
@@ -0,0 +1,389 @@
+// config file support, every key mirrors a command line flag
+use clap::{parser::ValueSource, ArgMatches, ValueEnum};
+use serde::{de::Error, Deserialize, Deserializer};
+use std::{collections::BTreeMap, env, path::PathBuf};
+use toml::Spanned;
+
+use crate::{check_grid, Settings};
+use weather_rs::geo::IpProvider;
+use weather_rs::units::{PrecipUnit, Preset, PressureUnit, WindUnit};
+use weather_rs::{
+    column::Column, coords, template::Template, EmojiMode, Format, Provider, TempScale, Vs16,
+    Window,
+};
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    week: Option<bool>,
+    short: Option<bool>,
+    debug: Option<bool>,
+    no_color: Option<bool>,
+    refresh: Option<bool>,
+    offline: Option<bool>,
+    fahrenheit: Option<bool>,
+    celsius: Option<bool>,
+    kelvin: Option<bool>,
+    #[serde(default, deserialize_with = "value_enum")]
+    dual: Option<TempScale>,
+    #[serde(default, deserialize_with = "value_enum")]
+    units: Option<Preset>,
+    #[serde(default, deserialize_with = "value_enum")]
+    wind_unit: Option<WindUnit>,
+    #[serde(default, deserialize_with = "value_enum")]
+    precip_unit: Option<PrecipUnit>,
+    #[serde(default, deserialize_with = "value_enum")]
+    pressure_unit: Option<PressureUnit>,
+    emoji: Option<EmojiMode>,
+    vs16: Option<Vs16>,
+    #[serde(default, deserialize_with = "value_enum")]
+    provider: Option<Provider>,
+    format: Option<Format>,
+    latlon: Option<String>,
+    location: Option<String>,
+    country: Option<String>,
+    lang: Option<String>,
+    private: Option<bool>,
+    grid: Option<f64>,
+    ip_providers: Option<Vec<IpProvider>>,
+    template: Option<Template>,
+    columns: Option<Vec<Column>>,
+    past_days: Option<u32>,
+    days: Option<u32>,
+    hours_back: Option<u32>,
+    hours_ahead: Option<u32>,
+}
+
+// a problem found in the config file
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub message: String,
+}
+
+// $XDG_CONFIG_HOME/weather-rs, falling back to ~/.config
+pub fn dir() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("weather-rs"))
+}
+
+pub fn default_path() -> Option<PathBuf> {
+    Some(dir()?.join("config.toml"))
+}
+
+// parses the value like its flag does, so the config takes exactly what the command line does
+fn value_enum<'de, D: Deserializer<'de>, T: ValueEnum>(d: D) -> Result<Option<T>, D::Error> {
+    let s = String::deserialize(d)?;
+    T::from_str(&s, false).map(Some).map_err(|_| {
+        let expected: Vec<String> = T::value_variants()
+            .iter()
+            .filter_map(|v| Some(v.to_possible_value()?.get_name().to_string()))
+            .collect();
+        D::Error::custom(format!(
+            "invalid value \"{s}\", expected one of: {}",
+            expected.join(", ")
+        ))
+    })
+}
+
+fn line_of(content: &str, offset: usize) -> usize {
+    content[..offset].matches('\n').count() + 1
+}
+
+// sets a field from the config unless its flag was passed on the command line
+macro_rules! merge {
+    ($config:ident, $settings:ident, $from_cli:ident, $($field:ident),*) => {
+        $(
+            if !$from_cli(stringify!($field)) {
+                if let Some(v) = $config.$field {
+                    $settings.$field = v;
+                }
+            }
+        )*
+    };
+}
+
+// like merge! for settings that are themselves optional
+macro_rules! merge_option {
+    ($config:ident, $settings:ident, $from_cli:ident, $($field:ident),*) => {
+        $(
+            if !$from_cli(stringify!($field)) && $config.$field.is_some() {
+                $settings.$field = $config.$field;
+            }
+        )*
+    };
+}
+
+impl Config {
+    // checks each key on its own so every bad entry is reported, not just the first
+    pub fn parse(content: &str) -> (Config, Vec<Diagnostic>) {
+        let entries: BTreeMap<Spanned<String>, Spanned<toml::Value>> = match toml::from_str(content)
+        {
+            Ok(entries) => entries,
+            Err(e) => {
+                let line = e.span().map_or(1, |s| line_of(content, s.start));
+                let message = e.message().to_string();
+                return (Config::default(), vec![Diagnostic { line, message }]);
+            }
+        };
+
+        let mut valid = toml::Table::new();
+        let mut diagnostics = vec![];
+        for (key, value) in entries {
+            let mut single = toml::Table::new();
+            single.insert(key.get_ref().clone(), value.get_ref().clone());
+            let checked = Config::deserialize(toml::Value::Table(single))
+                .map_err(|e| e.message().to_string())
+                .and_then(|single| single.check());
+            match checked {
+                Ok(()) => {
+                    valid.insert(key.into_inner(), value.into_inner());
+                }
+                Err(message) => {
+                    // unknown keys are named in the message already
+                    let message = match message.starts_with("unknown field") {
+                        true => message,
+                        false => format!("{}: {message}", key.get_ref()),
+                    };
+                    diagnostics.push(Diagnostic {
+                        line: line_of(content, key.span().start),
+                        message,
+                    })
+                }
+            }
+        }
+        diagnostics.sort_by_key(|d| d.line);
+
+        let config = Config::deserialize(toml::Value::Table(valid)).unwrap_or_default();
+        (config, diagnostics)
+    }
+
+    // what the command line checks once a value is used rather than while parsing it
+    fn check(&self) -> Result<(), String> {
+        if let Some(latlon) = &self.latlon {
+            coords::parse(latlon).map_err(|e| e.to_string())?;
+        }
+        if let Some(grid) = self.grid {
+            check_grid(grid)?;
+        }
+        if let Some(past_days) = self.past_days {
+            Window::check_past_days(past_days)?;
+        }
+        if let Some(days) = self.days {
+            Window::check_days(days)?;
+        }
+        if let Some(hours) = self.hours_back {
+            Window::check_hours_back(hours)?;
+        }
+        if let Some(hours) = self.hours_ahead {
+            Window::check_hours_ahead(hours)?;
+        }
+        Ok(())
+    }
+
+    // fills in every setting that wasn't given on the command line
+    pub fn apply(self, settings: &mut Settings, matches: &ArgMatches) {
+        let from_cli = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+
+        let config = self;
+        merge!(
+            config,
+            settings,
+            from_cli,
+            week,
+            short,
+            debug,
+            no_color,
+            refresh,
+            offline,
+            emoji,
+            vs16,
+            provider,
+            format,
+            columns,
+            private,
+            ip_providers,
+            past_days,
+            days,
+            hours_back,
+            hours_ahead
+        );
+
+        merge_option!(config, settings, from_cli, template, country, lang, grid, dual, units);
+
+        // a preset on the command line stands for every unit, so it replaces the config
+        // file's individual ones too
+        if !from_cli("units") {
+            merge_option!(
+                config,
+                settings,
+                from_cli,
+                wind_unit,
+                precip_unit,
+                pressure_unit
+            );
+        }
+        // these are exclusive, so any one on the command line overrides the others
+        let temperature_from_cli =
+            from_cli("fahrenheit") || from_cli("celsius") || from_cli("kelvin");
+        if !temperature_from_cli && !from_cli("units") {
+            merge!(config, settings, from_cli, fahrenheit, celsius, kelvin);
+        }
+        if !from_cli("latlon") && !from_cli("location") {
+            if config.latlon.is_some() {
+                settings.latlon = config.latlon;
+            } else if config.location.is_some() {
+                settings.location = config.location;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches};
+
+    // settings from `args` with `content` as the config file
+    fn settings(content: &str, args: &[&str]) -> Settings {
+        let matches = Settings::command()
+            .try_get_matches_from(["weather-rs"].iter().chain(args))
+            .unwrap();
+        let mut settings = Settings::from_arg_matches(&matches).unwrap();
+        Config::parse(content).0.apply(&mut settings, &matches);
+        settings
+    }
+
+    fn messages(content: &str) -> Vec<(usize, String)> {
+        let (_, diagnostics) = Config::parse(content);
+        diagnostics
+            .into_iter()
+            .map(|d| (d.line, d.message))
+            .collect()
+    }
+
+    #[test]
+    fn valid() {
+        let content =
+            "latlon = \"41.88,-87.63\"\nunits = \"imperial\"\nprovider = \"met-norway\"\n\
+                       pressure-unit = \"inhg\"\ndual = \"c\"\n";
+        assert!(messages(content).is_empty());
+        let (config, _) = Config::parse(content);
+        assert!(matches!(config.provider, Some(Provider::MetNorway)));
+        assert_eq!(config.pressure_unit, Some(PressureUnit::Inhg));
+    }
+
+    #[test]
+    fn latlon() {
+        let found = messages("emoji = \"nerd-font\"\nlatlon = \"banana\"\n");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, 2);
+        assert!(found[0]
+            .1
+            .starts_with("latlon: not a known coordinate format"));
+
+        let found = messages("latlon = \"91,0\"");
+        assert_eq!(found[0].1, "latlon: latitude 91 is outside -90 to 90");
+        // any format --latlon takes
+        assert!(messages("latlon = \"EN61fv\"").is_empty());
+    }
+
+    #[test]
+    fn value_enums() {
+        let found = messages("units = \"banana\"\nprovider = \"yr\"\nwind-unit = \"mps\"\n");
+        assert_eq!(
+            found,
+            [
+                (
+                    1,
+                    "units: invalid value \"banana\", expected one of: metric, imperial"
+                        .to_string()
+                ),
+                (
+                    2,
+                    "provider: invalid value \"yr\", expected one of: open-meteo, met-norway"
+                        .to_string()
+                ),
+                (
+                    3,
+                    "wind-unit: invalid value \"mps\", expected one of: kmh, ms, mph, kn"
+                        .to_string()
+                ),
+            ]
+        );
+        // the bad keys are left out, the rest still applies
+        let (config, _) = Config::parse("units = \"banana\"\nprecip-unit = \"inch\"\n");
+        assert!(config.units.is_none());
+        assert_eq!(config.precip_unit, Some(PrecipUnit::Inch));
+    }
+
+    #[test]
+    fn window() {
+        assert!(messages("past-days = 92\ndays = 16\nhours-back = 48\n").is_empty());
+        assert_eq!(
+            messages("past-days = 500\ndays = 0\nhours-ahead = 400\n"),
+            [
+                (
+                    1,
+                    "past-days: 500 is more than the 92 days open-meteo keeps".to_string()
+                ),
+                (2, "days: 0 is outside open-meteo's 1 to 16".to_string()),
+                (3, "hours-ahead: 400 is beyond any forecast".to_string()),
+            ]
+        );
+        let (config, _) = Config::parse("past-days = 500\ndays = 3\n");
+        assert_eq!(config.past_days, None);
+        assert_eq!(config.days, Some(3));
+
+        // the flags are held to the same limits
+        for args in [
+            ["--past-days", "500"],
+            ["--days", "0"],
+            ["--hours-ahead", "400"],
+        ] {
+            let parsed =
+                Settings::command().try_get_matches_from(["weather-rs"].iter().chain(&args));
+            assert!(parsed.is_err(), "{args:?}");
+        }
+    }
+
+    #[test]
+    fn grid() {
+        assert!(messages("grid = 0.25\n").is_empty());
+        for grid in ["-3", "0", "nan", "inf", "10"] {
+            let found = messages(&format!("grid = {grid}\n"));
+            assert!(found[0].1.starts_with("grid: "), "{grid}");
+            assert!(found[0].1.ends_with(" is outside 0 to 5 degrees"), "{grid}");
+        }
+    }
+
+    #[test]
+    fn units_from_cli() {
+        let content = "fahrenheit = true\nwind-unit = \"kn\"\n";
+        let s = settings(content, &[]);
+        assert_eq!(s.temp_scale(), TempScale::Fahrenheit);
+        assert_eq!(s.unit_system().wind, WindUnit::Kn);
+
+        // a preset on the command line beats the config's scale and units
+        let s = settings(content, &["--units", "metric"]);
+        assert_eq!(s.temp_scale(), TempScale::Celsius);
+        assert_eq!(s.unit_system().wind, WindUnit::Kmh);
+        // and a scale on the command line beats both
+        let s = settings(content, &["--units", "metric", "-k"]);
+        assert_eq!(s.temp_scale(), TempScale::Kelvin);
+        let s = settings("units = \"imperial\"\n", &["-c"]);
+        assert_eq!(s.temp_scale(), TempScale::Celsius);
+        assert_eq!(s.unit_system().wind, WindUnit::Mph);
+    }
+
+    #[test]
+    fn unknown_key() {
+        let found = messages("\ncolour = true\n");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, 2);
+        assert!(found[0].1.starts_with("unknown field `colour`"));
+    }
+}
@@ -0,0 +1,683 @@
+// coordinates as they get pasted from maps, GPS units and ham radio logs: decimal degrees,
+// degrees-minutes-seconds, geo: URIs, geohashes, plus codes, Maidenhead locators and UTM
+use std::str::FromStr;
+
+use crate::{LatLon, MyError};
+
+const GEOHASH_ALPHABET: &str = "0123456789bcdefghjkmnpqrstuvwxyz";
+const PLUS_CODE_ALPHABET: &str = "23456789CFGHJMPQRVWX";
+// latitude bands of UTM zones, C to M are south of the equator
+const UTM_BANDS: &str = "CDEFGHJKLMNPQRSTUVWX";
+
+// the coordinates `s` stands for, in whichever format it is in. locators and codes that name
+// an area give its center
+pub fn parse(s: &str) -> Result<LatLon, MyError> {
+    let s = s.trim();
+    let first = s.split_whitespace().next().unwrap_or_default();
+
+    if let Some(uri) = strip_prefix_ignore_case(s, "geo:") {
+        geo_uri(uri)
+    } else if let Some(hash) = strip_prefix_ignore_case(s, "geohash:") {
+        geohash(hash.trim())
+    } else if looks_like_plus_code(first) {
+        plus_code(s)
+    } else if looks_like_utm(s) {
+        utm(s)
+    } else if looks_like_maidenhead(s) {
+        maidenhead(s)
+    } else if looks_like_geohash(s) && !looks_like_hemispheres(s) {
+        geohash(s)
+    } else {
+        degrees(s)
+    }
+}
+
+impl FromStr for LatLon {
+    type Err = MyError;
+
+    fn from_str(s: &str) -> Result<Self, MyError> {
+        parse(s)
+    }
+}
+
+fn strip_prefix_ignore_case<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    match s.get(..prefix.len()) {
+        Some(start) if start.eq_ignore_ascii_case(prefix) => Some(&s[prefix.len()..]),
+        _ => None,
+    }
+}
+
+// an area given by its corners, as its center
+fn center(lat: (f64, f64), lon: (f64, f64)) -> Result<LatLon, MyError> {
+    LatLon::new((lat.0 + lat.1) / 2.0, (lon.0 + lon.1) / 2.0)
+}
+
+// "geo:41.88,-87.63" or "geo:41.88,-87.63,180;u=35", RFC 5870
+fn geo_uri(uri: &str) -> Result<LatLon, MyError> {
+    let fail = |reason: String| MyError::malformed("geo: URI", reason);
+    let (coordinates, params) = match uri.split_once(';') {
+        Some((c, p)) => (c, p),
+        None => (uri.split('?').next().unwrap_or_default(), ""),
+    };
+    for param in params.split(';') {
+        if let Some(crs) = strip_prefix_ignore_case(param, "crs=") {
+            if !crs.eq_ignore_ascii_case("wgs84") {
+                return Err(fail(format!("coordinate system {crs:?} isn't supported")));
+            }
+        }
+    }
+    let parts: Vec<&str> = coordinates.split(',').collect();
+    if !(2..=3).contains(&parts.len()) {
+        return Err(fail(format!(
+            "expected \"geo:lat,lon\", got {} values",
+            parts.len()
+        )));
+    }
+    let number = |name: &str, v: &str| {
+        v.trim()
+            .parse::<f64>()
+            .map_err(|_| fail(format!("{name} {v:?} is not a number")))
+    };
+    LatLon::new(
+        number("latitude", parts[0])?,
+        number("longitude", parts[1])?,
+    )
+}
+
+fn looks_like_geohash(s: &str) -> bool {
+    s.chars().any(|c| c.is_ascii_alphabetic())
+        && s.chars()
+            .all(|c| GEOHASH_ALPHABET.contains(c.to_ascii_lowercase()))
+}
+
+// one latitude and one longitude letter, "41N87W" is degrees even though it spells a geohash
+fn looks_like_hemispheres(s: &str) -> bool {
+    let letters: Vec<char> = s
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    match letters[..] {
+        [a, b] => matches!((a, b), ('N' | 'S', 'E' | 'W') | ('E' | 'W', 'N' | 'S')),
+        _ => false,
+    }
+}
+
+// "dp3wjztvx"
+fn geohash(hash: &str) -> Result<LatLon, MyError> {
+    let fail = |reason: String| MyError::malformed("geohash", reason);
+    if hash.is_empty() || hash.len() > 12 {
+        return Err(fail(format!(
+            "expected 1 to 12 characters, got {}",
+            hash.len()
+        )));
+    }
+    let mut lat = (-90.0, 90.0);
+    let mut lon = (-180.0, 180.0);
+    let mut is_lon = true;
+    for c in hash.chars() {
+        let bits = GEOHASH_ALPHABET
+            .find(c.to_ascii_lowercase())
+            .ok_or_else(|| fail(format!("{c:?} is not a geohash character")))?;
+        for bit in (0..5).rev() {
+            let range = if is_lon { &mut lon } else { &mut lat };
+            let mid = (range.0 + range.1) / 2.0;
+            match (bits >> bit) & 1 {
+                1 => range.0 = mid,
+                _ => range.1 = mid,
+            }
+            is_lon = !is_lon;
+        }
+    }
+    center(lat, lon)
+}
+
+// pairs alternate between letters and digits, "EN61" or "EN61fv"
+fn looks_like_maidenhead(s: &str) -> bool {
+    let chars: Vec<char> = s.chars().collect();
+    chars.len() >= 4
+        && chars.len().is_multiple_of(2)
+        && chars.chunks(2).enumerate().all(|(i, pair)| match i % 2 {
+            0 => pair.iter().all(|c| c.is_ascii_alphabetic()),
+            _ => pair.iter().all(|c| c.is_ascii_digit()),
+        })
+}
+
+// "EN61fv", fields of 20° by 10°, squares, subsquares and extended squares
+fn maidenhead(locator: &str) -> Result<LatLon, MyError> {
+    let fail = |reason: String| MyError::malformed("Maidenhead locator", reason);
+    let chars: Vec<char> = locator.to_ascii_uppercase().chars().collect();
+    if chars.len() > 10 {
+        return Err(fail(format!(
+            "expected at most 10 characters, got {}",
+            chars.len()
+        )));
+    }
+    let (mut lat, mut lon) = (-90.0, -180.0);
+    let (mut lat_size, mut lon_size) = (180.0, 360.0);
+    for (i, pair) in chars.chunks(2).enumerate() {
+        let (divisions, first) = match i {
+            0 => (18, 'A'),
+            _ if i % 2 == 1 => (10, '0'),
+            _ => (24, 'A'),
+        };
+        let last = (first as u8 + divisions - 1) as char;
+        let value = |c: char| match c as i32 - first as i32 {
+            v if (0..divisions as i32).contains(&v) => Ok(v as f64),
+            _ => Err(fail(format!("{c:?} is outside {first} to {last}"))),
+        };
+        lon_size /= divisions as f64;
+        lat_size /= divisions as f64;
+        lon += value(pair[0])? * lon_size;
+        lat += value(pair[1])? * lat_size;
+    }
+    center((lat, lat + lat_size), (lon, lon + lon_size))
+}
+
+fn looks_like_plus_code(s: &str) -> bool {
+    s.find('+').is_some_and(|i| i >= 2)
+        && s.chars()
+            .all(|c| c == '+' || c == '0' || PLUS_CODE_ALPHABET.contains(c.to_ascii_uppercase()))
+}
+
+// "86HJV9FH+2V", open location code
+fn plus_code(s: &str) -> Result<LatLon, MyError> {
+    let fail = |reason: String| MyError::malformed("plus code", reason);
+    let code = s.to_ascii_uppercase();
+    if code.split_whitespace().count() > 1 {
+        return Err(fail(
+            "short codes with a place name aren't supported, use the full code".to_string(),
+        ));
+    }
+    let (prefix, suffix) = code.split_once('+').unwrap_or((&code, ""));
+    if suffix.contains('+') {
+        return Err(fail("more than one \"+\"".to_string()));
+    }
+    if prefix.len() < 8 {
+        return Err(fail(format!(
+            "{s:?} is a short code, use the full code with 8 characters before the \"+\""
+        )));
+    }
+    if prefix.len() > 8 {
+        return Err(fail("expected 8 characters before the \"+\"".to_string()));
+    }
+    if suffix.len() == 1 {
+        return Err(fail(
+            "expected 2 or more characters after the \"+\"".to_string(),
+        ));
+    }
+    let digits = prefix.trim_end_matches('0');
+    if digits.len() % 2 == 1 || (digits.len() < 8 && !suffix.is_empty()) || digits.contains('0') {
+        return Err(fail("misplaced padding \"0\"".to_string()));
+    }
+
+    let value = |c: char| {
+        PLUS_CODE_ALPHABET
+            .find(c)
+            .map(|v| v as f64)
+            .ok_or_else(|| fail(format!("{c:?} is not a plus code character")))
+    };
+    let mut chars = digits.chars().chain(suffix.chars());
+    let (mut lat, mut lon) = (-90.0, -180.0);
+    let mut size = (20.0, 20.0);
+    // pairs of latitude and longitude digits in base 20
+    for i in 0..(digits.len() + suffix.len()).min(10) / 2 {
+        let (lat_digit, lon_digit) = (value(chars.next().unwrap())?, value(chars.next().unwrap())?);
+        if i == 0 && (lat_digit >= 9.0 || lon_digit >= 18.0) {
+            return Err(fail("outside the globe".to_string()));
+        }
+        if i > 0 {
+            size = (size.0 / 20.0, size.1 / 20.0);
+        }
+        lat += lat_digit * size.0;
+        lon += lon_digit * size.1;
+    }
+    // then a grid of 4 rows by 5 columns per character
+    for c in chars {
+        let v = value(c)?;
+        size = (size.0 / 4.0, size.1 / 5.0);
+        lat += (v / 5.0).floor() * size.0;
+        lon += (v % 5.0) * size.1;
+    }
+    center(
+        (lat, (lat + size.0).min(90.0)),
+        (lon, (lon + size.1).min(180.0)),
+    )
+}
+
+// a zone and band followed by two numbers of meters, "41 N 87 W" is degrees
+fn looks_like_utm(s: &str) -> bool {
+    let zone: String = s.chars().take_while(|c| c.is_ascii_digit()).collect();
+    let rest = s[zone.len()..].trim_start();
+    let meters: Vec<&str> = rest
+        .get(1..)
+        .unwrap_or_default()
+        .split_whitespace()
+        .collect();
+    (1..=2).contains(&zone.len())
+        && rest.starts_with(|c: char| c.is_ascii_alphabetic())
+        && meters.len() == 2
+        && meters
+            .iter()
+            .all(|m| m.chars().filter(|c| c.is_ascii_digit()).count() >= 5)
+}
+
+// "16T 447600 4636000", WGS84 only
+fn utm(s: &str) -> Result<LatLon, MyError> {
+    let fail = |reason: String| MyError::malformed("UTM", reason);
+    let zone_len = s.chars().take_while(|c| c.is_ascii_digit()).count();
+    let zone: u32 = s[..zone_len].parse().unwrap_or(0);
+    if !(1..=60).contains(&zone) {
+        return Err(fail(format!("zone {zone} is outside 1 to 60")));
+    }
+    let rest = s[zone_len..].trim_start();
+    let band = rest.chars().next().unwrap_or_default().to_ascii_uppercase();
+    let Some(band_index) = UTM_BANDS.find(band) else {
+        return Err(fail(format!("{band:?} is not a latitude band, C to X")));
+    };
+    let numbers: Vec<&str> = rest[1..].split_whitespace().collect();
+    let [easting, northing] = numbers[..] else {
+        return Err(fail(
+            "expected a zone, an easting and a northing like \"16T 447600 4636000\"".to_string(),
+        ));
+    };
+    let meters = |name: &str, v: &str, suffix: char| {
+        v.trim_end_matches(['m', suffix, suffix.to_ascii_lowercase()])
+            .parse::<f64>()
+            .map_err(|_| fail(format!("{name} {v:?} is not a number of meters")))
+    };
+    let easting = meters("easting", easting, 'E')?;
+    let northing = meters("northing", northing, 'N')?;
+    if !(100_000.0..=900_000.0).contains(&easting) {
+        return Err(fail(format!(
+            "easting {easting} is outside 100000 to 900000"
+        )));
+    }
+    if !(0.0..=10_000_000.0).contains(&northing) {
+        return Err(fail(format!(
+            "northing {northing} is outside 0 to 10000000"
+        )));
+    }
+    let north = band_index >= UTM_BANDS.find('N').unwrap();
+
+    // inverse transverse mercator, Snyder's Map Projections p. 63
+    let k0 = 0.9996;
+    let a = 6_378_137.0;
+    let f = 1.0 / 298.257_223_563;
+    let e2: f64 = f * (2.0 - f);
+    let ep2 = e2 / (1.0 - e2);
+    let x = easting - 500_000.0;
+    let y = if north {
+        northing
+    } else {
+        northing - 10_000_000.0
+    };
+
+    let m = y / k0;
+    let mu = m / (a * (1.0 - e2 / 4.0 - 3.0 * e2.powi(2) / 64.0 - 5.0 * e2.powi(3) / 256.0));
+    let e1 = (1.0 - (1.0 - e2).sqrt()) / (1.0 + (1.0 - e2).sqrt());
+    let phi1 = mu
+        + (3.0 * e1 / 2.0 - 27.0 * e1.powi(3) / 32.0) * (2.0 * mu).sin()
+        + (21.0 * e1.powi(2) / 16.0 - 55.0 * e1.powi(4) / 32.0) * (4.0 * mu).sin()
+        + (151.0 * e1.powi(3) / 96.0) * (6.0 * mu).sin()
+        + (1097.0 * e1.powi(4) / 512.0) * (8.0 * mu).sin();
+
+    let (sin, cos, tan) = (phi1.sin(), phi1.cos(), phi1.tan());
+    let n1 = a / (1.0 - e2 * sin * sin).sqrt();
+    let t1 = tan * tan;
+    let c1 = ep2 * cos * cos;
+    let r1 = a * (1.0 - e2) / (1.0 - e2 * sin * sin).powf(1.5);
+    let d = x / (n1 * k0);
+
+    let lat = phi1
+        - (n1 * tan / r1)
+            * (d.powi(2) / 2.0
+                - (5.0 + 3.0 * t1 + 10.0 * c1 - 4.0 * c1 * c1 - 9.0 * ep2) * d.powi(4) / 24.0
+                + (61.0 + 90.0 * t1 + 298.0 * c1 + 45.0 * t1 * t1 - 252.0 * ep2 - 3.0 * c1 * c1)
+                    * d.powi(6)
+                    / 720.0);
+    let lon = (d - (1.0 + 2.0 * t1 + c1) * d.powi(3) / 6.0
+        + (5.0 - 2.0 * c1 + 28.0 * t1 - 3.0 * c1 * c1 + 8.0 * ep2 + 24.0 * t1 * t1) * d.powi(5)
+            / 120.0)
+        / cos;
+    let central_meridian = zone as f64 * 6.0 - 183.0;
+
+    let lat = lat.to_degrees();
+    // bands are 8° from 80°S, X is 12°
+    let band_south = -80.0 + band_index as f64 * 8.0;
+    if lat < band_south - 0.5 || lat > band_south + if band == 'X' { 12.5 } else { 8.5 } {
+        return Err(fail(format!(
+            "northing {northing} is outside band {band} at latitude {lat:.2}"
+        )));
+    }
+    LatLon::new(lat, central_meridian + lon.to_degrees())
+}
+
+#[derive(Debug)]
+enum Token {
+    // the value with the unit symbol following it: 0 degrees, 1 minutes, 2 seconds
+    Number(f64, Option<usize>),
+    Hemisphere(char),
+    Separator,
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, MyError> {
+    let mut tokens = vec![];
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '0'..='9' | '.' | '-' | '+' => {
+                let mut number = c.to_string();
+                while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit() || **c == '.') {
+                    number.push(c);
+                    chars.next();
+                }
+                let value = number.parse().map_err(|_| {
+                    MyError::malformed("coordinates", format!("{number:?} is not a number"))
+                })?;
+                tokens.push(Token::Number(value, None));
+            }
+            '°' | 'º' | '˚' | '\'' | '′' | '’' | '"' | '″' | '”' => {
+                let unit = match c {
+                    '°' | 'º' | '˚' => 0,
+                    // two single quotes are often typed for seconds
+                    '\'' | '′' | '’' if chars.peek() == Some(&c) => {
+                        chars.next();
+                        2
+                    }
+                    '\'' | '′' | '’' => 1,
+                    _ => 2,
+                };
+                match tokens.last_mut() {
+                    Some(Token::Number(_, u @ None)) => *u = Some(unit),
+                    _ => {
+                        return Err(MyError::malformed(
+                            "coordinates",
+                            format!("{c:?} doesn't follow a number"),
+                        ))
+                    }
+                }
+            }
+            'N' | 'S' | 'E' | 'W' | 'n' | 's' | 'e' | 'w' => {
+                tokens.push(Token::Hemisphere(c.to_ascii_uppercase()))
+            }
+            ',' | ';' => tokens.push(Token::Separator),
+            c if c.is_whitespace() => {}
+            c if c.is_alphabetic() => return Err(MyError::UnknownFormat),
+            c => {
+                return Err(MyError::malformed(
+                    "coordinates",
+                    format!("unexpected {c:?}"),
+                ))
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+// splits the tokens of a pair of coordinates at the comma, or between the hemispheres
+fn split(tokens: &[Token]) -> Result<(&[Token], &[Token]), MyError> {
+    let fail = |reason: &str| MyError::malformed("coordinates", reason);
+    let separators: Vec<usize> = (0..tokens.len())
+        .filter(|i| matches!(tokens[*i], Token::Separator))
+        .collect();
+    let hemispheres: Vec<usize> = (0..tokens.len())
+        .filter(|i| matches!(tokens[*i], Token::Hemisphere(_)))
+        .collect();
+    match (&separators[..], &hemispheres[..]) {
+        (&[i], _) => Ok((&tokens[..i], &tokens[i + 1..])),
+        ([], &[0, i]) => Ok(tokens.split_at(i)),
+        ([], &[i, _]) => Ok(tokens.split_at(i + 1)),
+        ([], []) if tokens.len().is_multiple_of(2) => Ok(tokens.split_at(tokens.len() / 2)),
+        ([], []) | ([], [_]) => Err(fail("expected a latitude and a longitude")),
+        ([], _) => Err(fail("expected one hemisphere letter for each coordinate")),
+        _ => Err(fail("expected one comma between latitude and longitude")),
+    }
+}
+
+// one coordinate like "41°52'55\"N", "-87.63" or "W 87 37 40", and its hemisphere
+fn coordinate(tokens: &[Token]) -> Result<(f64, Option<char>), MyError> {
+    let fail = |reason: String| MyError::malformed("coordinates", reason);
+    let mut hemisphere = None;
+    let mut parts = vec![];
+    for token in tokens {
+        match token {
+            Token::Number(value, unit) => parts.push((*value, *unit)),
+            Token::Hemisphere(h) if hemisphere.is_none() => hemisphere = Some(*h),
+            _ => {
+                return Err(fail(
+                    "expected one hemisphere letter per coordinate".to_string(),
+                ))
+            }
+        }
+    }
+    if parts.is_empty() {
+        return Err(fail("a coordinate is missing its degrees".to_string()));
+    }
+    if parts.len() > 3 {
+        return Err(fail(format!(
+            "expected degrees, minutes and seconds, got {} numbers",
+            parts.len()
+        )));
+    }
+
+    let names = ["degrees", "minutes", "seconds"];
+    let mut value = 0.0;
+    for (i, (part, unit)) in parts.iter().enumerate() {
+        if unit.is_some_and(|u| u != i) {
+            return Err(fail(format!(
+                "{} where {} were expected",
+                names[unit.unwrap()],
+                names[i]
+            )));
+        }
+        if i > 0 && !(0.0..60.0).contains(part) {
+            return Err(fail(format!("{} {part} are outside 0 to 60", names[i])));
+        }
+        if i + 1 < parts.len() && part.fract() != 0.0 {
+            return Err(fail(format!(
+                "{} {part} have decimals but are followed by {}",
+                names[i],
+                names[i + 1]
+            )));
+        }
+        value += part.abs() / 60f64.powi(i as i32);
+    }
+    let negative = parts[0].0.is_sign_negative();
+    if negative && hemisphere.is_some() {
+        return Err(fail(
+            "a coordinate has both a sign and a hemisphere letter".to_string(),
+        ));
+    }
+    match negative || matches!(hemisphere, Some('S' | 'W')) {
+        true => Ok((-value, hemisphere)),
+        false => Ok((value, hemisphere)),
+    }
+}
+
+// "41.88,-87.63", "41°52'55\"N 87°37'40\"W" and most things in between
+fn degrees(s: &str) -> Result<LatLon, MyError> {
+    if !s.chars().any(|c| c.is_ascii_digit()) {
+        return Err(MyError::UnknownFormat);
+    }
+    let tokens = tokenize(s)?;
+    let (first, second) = split(&tokens)?;
+    let ((a, h1), (b, h2)) = (coordinate(first)?, coordinate(second)?);
+    let is_lat = |h: Option<char>| matches!(h, Some('N' | 'S'));
+    let is_lon = |h: Option<char>| matches!(h, Some('E' | 'W'));
+    if (is_lat(h1) && is_lat(h2)) || (is_lon(h1) && is_lon(h2)) {
+        let axis = if is_lat(h1) {
+            "latitudes"
+        } else {
+            "longitudes"
+        };
+        return Err(MyError::malformed(
+            "coordinates",
+            format!("both coordinates are {axis}"),
+        ));
+    }
+    // longitude first, as in "87°37'40\"W 41°52'55\"N"
+    match is_lon(h1) || is_lat(h2) {
+        true => LatLon::new(b, a),
+        false => LatLon::new(a, b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> (f64, f64) {
+        let latlon = parse(s).unwrap_or_else(|e| panic!("{s:?}: {e}"));
+        (latlon.lat, latlon.lon)
+    }
+
+    fn assert_near(s: &str, expected: (f64, f64), tolerance: f64) {
+        let (lat, lon) = at(s);
+        assert!(
+            (lat - expected.0).abs() < tolerance && (lon - expected.1).abs() < tolerance,
+            "{s:?} is {lat},{lon}"
+        );
+    }
+
+    fn error(s: &str) -> String {
+        match parse(s) {
+            Ok(l) => panic!("{s:?} parsed as {},{}", l.lat, l.lon),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    // the Chicago loop, as written in each format
+    const CHICAGO: (f64, f64) = (41.8781, -87.6298);
+
+    #[test]
+    fn decimal_degrees() {
+        assert_eq!(at("41.8781,-87.6298"), CHICAGO);
+        assert_eq!(at("41.8781 -87.6298"), CHICAGO);
+        assert_eq!(at(" 41.8781, -87.6298 "), CHICAGO);
+        assert_eq!(at("41.8781;-87.6298"), CHICAGO);
+        assert_eq!(at("-33.9249,18.4241"), (-33.9249, 18.4241));
+    }
+
+    #[test]
+    fn degrees_minutes_seconds() {
+        let dms = (41.87806, -87.62972);
+        assert_near("41°52'41\"N 87°37'47\"W", dms, 1e-4);
+        assert_near("41°52′41″N, 87°37′47″W", dms, 1e-4);
+        assert_near("41°52'41''N 87°37'47''W", dms, 1e-4);
+        assert_near("41 52 41 N 87 37 47 W", dms, 1e-4);
+        assert_near("41 52.7N, 87 37.8W", (41.87833, -87.63), 1e-4);
+        assert_eq!(at("N41.878 W87.630"), (41.878, -87.63));
+        assert_eq!(at("41N 87W"), (41.0, -87.0));
+        assert_eq!(at("33S 18E"), (-33.0, 18.0));
+        // either coordinate may come first with hemisphere letters
+        assert_near("87°37'47\"W 41°52'41\"N", dms, 1e-4);
+        assert_eq!(at("W87.63 N41.88"), (41.88, -87.63));
+    }
+
+    #[test]
+    fn geo_uri() {
+        assert_eq!(at("geo:41.8781,-87.6298"), CHICAGO);
+        assert_eq!(at("GEO:41.8781,-87.6298"), CHICAGO);
+        // altitude and parameters are ignored
+        assert_eq!(at("geo:41.8781,-87.6298,180;u=35"), CHICAGO);
+        assert_eq!(at("geo:41.8781,-87.6298;crs=wgs84"), CHICAGO);
+        assert_eq!(at("geo:41.8781,-87.6298?z=12"), CHICAGO);
+    }
+
+    #[test]
+    fn geohash() {
+        assert_near("dp3wjztvx", CHICAGO, 1e-4);
+        assert_near("DP3WJZTVX", CHICAGO, 1e-4);
+        // a short hash is a big cell, its center
+        assert_eq!(at("s"), (22.5, 22.5));
+        assert_near("geohash:dp3w", (41.92383, -87.71484), 1e-4);
+    }
+
+    #[test]
+    fn plus_code() {
+        assert_near("86HJV9HC+63", CHICAGO, 1e-4);
+        assert_near("86hjv9hc+63", CHICAGO, 1e-4);
+        // grid refinement past 10 digits
+        assert_near("86HJV9HC+63X", CHICAGO, 1e-4);
+        // padded codes name a bigger area
+        assert_eq!(at("86HJ0000+"), (41.5, -87.5));
+    }
+
+    #[test]
+    fn maidenhead() {
+        assert_eq!(at("EN61"), (41.5, -87.0));
+        assert_near("EN61fv", (41.89583, -87.54167), 1e-4);
+        assert_near("EN61fv55", (41.89792, -87.5375), 1e-4);
+        assert_eq!(at("AA00"), (-89.5, -179.0));
+        assert_eq!(at("RR99"), (89.5, 179.0));
+    }
+
+    #[test]
+    fn utm() {
+        assert_near("16T 447742 4636434", CHICAGO, 1e-4);
+        assert_near("16t 447742m 4636434m", CHICAGO, 1e-4);
+        assert_near("16 T 447742E 4636434N", CHICAGO, 1e-4);
+        // bands C to M are south of the equator
+        assert_near("34H 261878 6243186", (-33.9249, 18.4241), 1e-3);
+    }
+
+    #[test]
+    fn ambiguous() {
+        // hemisphere letters are a geohash's letters too, one of each is degrees
+        assert_eq!(at("41N87W"), (41.0, -87.0));
+        assert_eq!(at("41n87w"), (41.0, -87.0));
+        assert_eq!(at("87W41N"), (41.0, -87.0));
+        assert_ne!(at("geohash:41n87w"), (41.0, -87.0));
+        // more letters than hemispheres is still a geohash
+        assert_near("dp3wjztvx", CHICAGO, 1e-4);
+        // a locator before a geohash, unless asked for
+        assert_eq!(at("EN61"), (41.5, -87.0));
+        assert_eq!(at("en61"), (41.5, -87.0));
+        assert_near("geohash:en61", (35.41992, -42.01172), 1e-4);
+        // "41 N 87 W" has a band letter and two numbers, but isn't UTM
+        assert_eq!(at("41 N 87 W"), (41.0, -87.0));
+    }
+
+    #[test]
+    fn errors() {
+        let cases = [
+            ("banana", "not a known coordinate format"),
+            ("", "not a known coordinate format"),
+            ("91,0", "latitude 91 is outside -90 to 90"),
+            ("0,181", "longitude 181 is outside -180 to 180"),
+            ("41", "invalid coordinates: expected a latitude and a longitude"),
+            ("41,87,3", "invalid coordinates: expected one comma between latitude and longitude"),
+            ("41N 42N", "invalid coordinates: both coordinates are latitudes"),
+            ("87W 88E", "invalid coordinates: both coordinates are longitudes"),
+            ("-41 N, 87 W", "invalid coordinates: a coordinate has both a sign and a hemisphere letter"),
+            ("41 61' N, 87 W", "invalid coordinates: minutes 61 are outside 0 to 60"),
+            ("41.5 30' N, 87 W", "invalid coordinates: degrees 41.5 have decimals but are followed by minutes"),
+            ("41' 30, 87", "invalid coordinates: minutes where degrees were expected"),
+            ("41°52'41\"1 N, 87 W", "invalid coordinates: expected degrees, minutes and seconds, got 4 numbers"),
+            ("°41, 87", "invalid coordinates: '°' doesn't follow a number"),
+            ("41.8.7, 87", "invalid coordinates: \"41.8.7\" is not a number"),
+            ("41 # 87", "invalid coordinates: unexpected '#'"),
+            ("geo:41.8781", "invalid geo: URI: expected \"geo:lat,lon\", got 1 values"),
+            ("geo:north,-87.6298", "invalid geo: URI: latitude \"north\" is not a number"),
+            ("geo:41.8781,-87.6298;crs=nad27", "invalid geo: URI: coordinate system \"nad27\" isn't supported"),
+            ("zzzzzzzzzzzzz", "invalid geohash: expected 1 to 12 characters, got 13"),
+            ("geohash:dp3a", "invalid geohash: 'a' is not a geohash character"),
+            ("SS00", "invalid Maidenhead locator: 'S' is outside A to R"),
+            ("EN61fz", "invalid Maidenhead locator: 'Z' is outside A to X"),
+            ("V9HC+63", "invalid plus code: \"V9HC+63\" is a short code, use the full code with 8 characters before the \"+\""),
+            ("V9HC+63 Chicago", "invalid plus code: short codes with a place name aren't supported, use the full code"),
+            ("86HJV9HC+6", "invalid plus code: expected 2 or more characters after the \"+\""),
+            ("86HJV9HC++63", "invalid plus code: more than one \"+\""),
+            ("86H0V9HC+63", "invalid plus code: misplaced padding \"0\""),
+            ("X6HJV9HC+63", "invalid plus code: outside the globe"),
+            ("61T 447742 4636434", "invalid UTM: zone 61 is outside 1 to 60"),
+            ("16Z 447742 4636434", "invalid UTM: 'Z' is not a latitude band, C to X"),
+            ("16T 47742 4636434", "invalid UTM: easting 47742 is outside 100000 to 900000"),
+            ("16C 447742 4636434", "invalid UTM: northing 4636434 is outside band C at latitude -48.42"),
+        ];
+        for (s, message) in cases {
+            assert!(error(s).starts_with(message), "{s:?}: {}", error(s));
+        }
+    }
+}
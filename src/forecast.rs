@@ -0,0 +1,271 @@
+// provider independent forecast, what every WeatherProvider normalizes into
+// and what gets cached and rendered
+use chrono::{DateTime, FixedOffset, NaiveTime, TimeZone};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+use crate::compute_wet_bulb;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Forecast {
+    // WeatherProvider::name of the source
+    pub provider: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub elevation: f64,
+    pub timezone: String,
+    pub timezone_abbreviation: String,
+    pub utc_offset_seconds: i64,
+    pub units: Units,
+    pub current: Current,
+    pub hourly: Series,
+    pub minutely_15: Series,
+    pub daily: Daily,
+}
+
+// unit labels of the values, temperatures are always "°C"
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Units {
+    pub temperature: String,
+    pub relative_humidity: String,
+    pub precipitation_probability: String,
+    pub wind_speed: String,
+    pub wind_direction: String,
+    // empty in forecasts cached before these were fetched
+    #[serde(default)]
+    pub precipitation: String,
+    #[serde(default)]
+    pub pressure: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Current {
+    pub time: u32,
+    pub temperature_2m: f64,
+    pub relative_humidity_2m: f64,
+    pub weather_code: u8,
+}
+
+// evenly spaced samples, all vectors have the same length as `time`
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Series {
+    pub time: Vec<u32>,
+    pub temperature_2m: Vec<f64>,
+    pub relative_humidity_2m: Vec<f64>,
+    pub dew_point_2m: Vec<f64>,
+    // empty in forecasts cached before it was fetched
+    #[serde(default)]
+    pub apparent_temperature: Vec<f64>,
+    // amount since the previous sample
+    #[serde(default)]
+    pub precipitation: Vec<f64>,
+    #[serde(default)]
+    pub pressure_msl: Vec<f64>,
+    pub precipitation_probability: Vec<f64>,
+    pub weather_code: Vec<u8>,
+    pub wind_speed_10m: Vec<f64>,
+    pub wind_direction_10m: Vec<i16>,
+}
+
+// one entry per local day, `time` is the local midnight starting it
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Daily {
+    pub time: Vec<u32>,
+    pub temperature_2m_max: Vec<f64>,
+    pub temperature_2m_min: Vec<f64>,
+    // aggregates for days past HOURLY_DAYS, empty in forecasts cached before they were fetched
+    #[serde(default)]
+    pub temperature_2m_mean: Vec<f64>,
+    #[serde(default)]
+    pub relative_humidity_2m_max: Vec<f64>,
+    #[serde(default)]
+    pub relative_humidity_2m_min: Vec<f64>,
+    #[serde(default)]
+    pub relative_humidity_2m_mean: Vec<f64>,
+    #[serde(default)]
+    pub wind_speed_10m_min: Vec<f64>,
+    #[serde(default)]
+    pub wind_speed_10m_mean: Vec<f64>,
+    pub sunrise: Vec<u32>,
+    pub sunset: Vec<u32>,
+    pub precipitation_probability_max: Vec<i32>,
+    pub wind_speed_10m_max: Vec<f64>,
+    pub weather_code: Vec<u8>,
+    pub uv_index_max: Vec<f64>,
+}
+
+// days from today the weekly view aggregates from hourly samples, after that it uses the
+// provider's daily aggregates
+pub const HOURLY_DAYS: usize = 7;
+
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct Stats {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+}
+
+impl Stats {
+    pub fn of(values: &[f64]) -> Self {
+        Stats {
+            min: values.iter().copied().reduce(f64::min).unwrap_or(f64::NAN),
+            max: values.iter().copied().reduce(f64::max).unwrap_or(f64::NAN),
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+        }
+    }
+
+    // each value passed through `f`, which has to preserve their order
+    pub fn map(self, f: impl Fn(f64) -> f64) -> Self {
+        Stats {
+            min: f(self.min),
+            max: f(self.max),
+            mean: f(self.mean),
+        }
+    }
+}
+
+// what the weekly view shows for one entry in `daily`
+pub struct DayStats {
+    pub temperature: Stats,
+    pub relative_humidity: Stats,
+    // needs the samples, so None for days built from daily aggregates
+    pub wet_bulb: Option<Stats>,
+    pub wind_speed: Stats,
+}
+
+impl Forecast {
+    // index into `daily` of the day containing `time`
+    pub fn day_index(&self, time: u32) -> usize {
+        self.daily
+            .time
+            .iter()
+            .rposition(|t| *t <= time)
+            .unwrap_or(0)
+    }
+
+    // sea level pressure during the hour containing `time`, only fetched hourly
+    pub fn pressure_at(&self, time: u32) -> Option<f64> {
+        let i = self.hourly.time.iter().rposition(|t| *t <= time)?;
+        self.hourly.pressure_msl.get(i).copied()
+    }
+
+    fn tz(&self) -> Option<Tz> {
+        self.timezone.parse().ok()
+    }
+
+    // `time` as wall clock time at the location, following its DST rules when the
+    // timezone is a known IANA name and the offset from fetch time otherwise
+    pub fn local_time(&self, time: u32) -> DateTime<FixedOffset> {
+        let utc = DateTime::from_timestamp(time as i64, 0).unwrap_or_default();
+        match self.tz() {
+            Some(tz) => utc.with_timezone(&tz).fixed_offset(),
+            None => {
+                let offset = FixedOffset::east_opt(self.utc_offset_seconds as i32)
+                    .unwrap_or(FixedOffset::east_opt(0).unwrap());
+                utc.with_timezone(&offset)
+            }
+        }
+    }
+
+    // the local midnight ending the day that contains `time`
+    fn next_midnight(&self, time: u32) -> u32 {
+        let local = self.local_time(time);
+        let midnight = local
+            .date_naive()
+            .succ_opt()
+            .map(|d| d.and_time(NaiveTime::MIN));
+        let next = match (midnight, self.tz()) {
+            (Some(m), Some(tz)) => tz.from_local_datetime(&m).earliest().map(|t| t.timestamp()),
+            (Some(m), None) => local
+                .timezone()
+                .from_local_datetime(&m)
+                .earliest()
+                .map(|t| t.timestamp()),
+            (None, _) => None,
+        };
+        next.map_or(time + 86400, |t| t as u32)
+    }
+
+    // the samples of `time` belonging to each entry in `daily`, split at local midnights
+    // so days around DST changes are 23 or 25 hours long
+    pub fn day_ranges(&self, time: &[u32]) -> Vec<Range<usize>> {
+        let days = &self.daily.time;
+        days.iter()
+            .enumerate()
+            .map(|(i, start)| {
+                let end = days
+                    .get(i + 1)
+                    .copied()
+                    .unwrap_or_else(|| self.next_midnight(*start));
+                time.partition_point(|t| t < start)..time.partition_point(|t| *t < end)
+            })
+            .collect()
+    }
+
+    // one entry per day in `daily`: hourly samples up to HOURLY_DAYS after the day
+    // containing `now`, the daily aggregates from there on. forecasts missing either fall
+    // back to the other
+    pub fn days(&self, now: u32) -> Vec<DayStats> {
+        let h = &self.hourly;
+        let d = &self.daily;
+        let today = self.day_index(now);
+        let ranges = self.day_ranges(&h.time);
+
+        ranges
+            .into_iter()
+            .enumerate()
+            .map(|(i, range)| {
+                let aggregate = (
+                    d.temperature_2m_mean.get(i),
+                    d.relative_humidity_2m_min.get(i),
+                    d.relative_humidity_2m_max.get(i),
+                    d.relative_humidity_2m_mean.get(i),
+                    d.wind_speed_10m_min.get(i),
+                    d.wind_speed_10m_mean.get(i),
+                );
+                match aggregate {
+                    (
+                        Some(t),
+                        Some(h_min),
+                        Some(h_max),
+                        Some(h_mean),
+                        Some(w_min),
+                        Some(w_mean),
+                    ) if i >= today + HOURLY_DAYS || range.is_empty() => DayStats {
+                        temperature: Stats {
+                            min: d.temperature_2m_min[i],
+                            max: d.temperature_2m_max[i],
+                            mean: *t,
+                        },
+                        relative_humidity: Stats {
+                            min: *h_min,
+                            max: *h_max,
+                            mean: *h_mean,
+                        },
+                        wet_bulb: None,
+                        wind_speed: Stats {
+                            min: *w_min,
+                            max: d.wind_speed_10m_max[i],
+                            mean: *w_mean,
+                        },
+                    },
+                    _ => {
+                        let wet_bulbs: Vec<f64> = range
+                            .clone()
+                            .map(|j| {
+                                compute_wet_bulb(h.temperature_2m[j], h.relative_humidity_2m[j])
+                            })
+                            .collect();
+                        DayStats {
+                            temperature: Stats::of(&h.temperature_2m[range.clone()]),
+                            relative_humidity: Stats::of(&h.relative_humidity_2m[range.clone()]),
+                            wet_bulb: Some(Stats::of(&wet_bulbs)),
+                            wind_speed: Stats::of(&h.wind_speed_10m[range]),
+                        }
+                    }
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,470 @@
+// finding out where to fetch a forecast for: by name, by ip address or from coordinates
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, env, fs, path::PathBuf, str::FromStr, time::Duration};
+
+use crate::provider::Place;
+use crate::structs::{GeocodingResponse, IfconfigResponse, IpApiResponse, IpinfoResponse};
+use crate::{geonames, request_api, request_checked, LatLon};
+
+pub use crate::structs::GeocodingResult;
+
+// each ip provider gets this long before the next one is asked
+const IP_TIMEOUT: Duration = Duration::from_secs(5);
+
+// how long the ip location is trusted while the network stays the same
+const IP_TTL: u64 = 12 * 3600;
+
+// url for open-meteo geocoding
+const GEOCODING_URL: &str = "https://geocoding-api.open-meteo.com/v1/search";
+
+// candidates asked for when searching by name
+const SEARCH_COUNT: usize = 10;
+
+// how far coordinates can be from a place to be called by its name
+const NEAR_KM: f64 = 50.0;
+
+// narrows down and localizes a search by name
+#[derive(Clone, Debug, Default)]
+pub struct SearchOptions {
+    // ISO 3166-1 alpha-2 code like "US"
+    pub country: Option<String>,
+    // language of the returned names like "de", english by default
+    pub lang: Option<String>,
+    // degrees the coordinates of results are rounded to, see LatLon::snap
+    pub grid: Option<f64>,
+}
+
+// places matching a name like "Springfield", most relevant first. the downloaded GeoNames
+// places are searched first and Open-Meteo's geocoding only when none of them match
+pub fn search(name: &str, opts: &SearchOptions) -> Result<Vec<GeocodingResult>> {
+    let results = match geonames::load() {
+        Some(cities) => geonames::search(&cities, name, opts),
+        None => vec![],
+    };
+    let mut results = match results.is_empty() {
+        true => search_online(name, opts)?,
+        false => {
+            debug!("{} matches in the GeoNames places.", results.len());
+            results
+        }
+    };
+    results.truncate(SEARCH_COUNT);
+    for r in &mut results {
+        let latlon = LatLon {
+            lat: r.latitude,
+            lon: r.longitude,
+        }
+        .snap(opts.grid);
+        r.latitude = latlon.lat;
+        r.longitude = latlon.lon;
+    }
+    Ok(results)
+}
+
+// the geocoding request for `name`, every parameter percent-encoded
+fn search_url(name: &str, opts: &SearchOptions) -> String {
+    let count = SEARCH_COUNT.to_string();
+    let country = opts.country.as_deref().map(str::to_uppercase);
+    let mut params = vec![
+        ("name", name),
+        ("count", &count),
+        ("language", opts.lang.as_deref().unwrap_or("en")),
+        ("format", "json"),
+    ];
+    if let Some(country) = &country {
+        params.push(("countryCode", country));
+    }
+    let url = reqwest::Url::parse_with_params(GEOCODING_URL, &params).expect("valid base url");
+    // spaces come out as '+', which request_api would escape as a literal plus
+    url.as_str().replace('+', "%20")
+}
+
+fn search_online(name: &str, opts: &SearchOptions) -> Result<Vec<GeocodingResult>> {
+    let url = search_url(name, opts);
+    let response: GeocodingResponse =
+        request_api(&url).map_err(|e| anyhow!("failed to reach geocoding API: {e}"))?;
+    match response.results {
+        Some(results) if !results.is_empty() => Ok(results),
+        _ => Err(anyhow!("no results found for location \"{name}\"")),
+    }
+}
+
+// the best match for a place name like "Chicago" or "Tokyo"
+pub fn geocode(name: &str) -> Result<GeocodingResult> {
+    Ok(search(name, &SearchOptions::default())?.remove(0))
+}
+
+// $XDG_STATE_HOME/weather-rs, falling back to ~/.local/state
+fn state_dir() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".local/state"),
+    };
+    Some(base.join("weather-rs"))
+}
+
+fn picks_path() -> Option<PathBuf> {
+    Some(state_dir()?.join("places.json"))
+}
+
+fn pick_key(name: &str, opts: &SearchOptions) -> String {
+    let country = opts.country.as_deref().unwrap_or_default();
+    format!("{}|{}", name.trim().to_lowercase(), country.to_lowercase())
+}
+
+fn picks() -> BTreeMap<String, GeocodingResult> {
+    picks_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+// the candidate chosen the last time `name` was searched for with the same country
+pub fn remembered(name: &str, opts: &SearchOptions) -> Option<GeocodingResult> {
+    picks().remove(&pick_key(name, opts))
+}
+
+// answers later searches for `name` with `result` without asking again
+pub fn remember(name: &str, opts: &SearchOptions, result: &GeocodingResult) -> Result<()> {
+    let path = picks_path().ok_or_else(|| anyhow!("could not determine state directory"))?;
+    let mut picks = picks();
+    picks.insert(pick_key(name, opts), result.clone());
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(path, serde_json::to_string_pretty(&picks)?)?;
+    Ok(())
+}
+
+impl From<GeocodingResult> for Place {
+    fn from(result: GeocodingResult) -> Self {
+        Place {
+            latlon: LatLon {
+                lat: result.latitude,
+                lon: result.longitude,
+            },
+            timezone: result.timezone,
+        }
+    }
+}
+
+// the last ip location and the network it was looked up from
+#[derive(Serialize, Deserialize, Debug)]
+struct IpLocation {
+    lat: f64,
+    lon: f64,
+    timezone: String,
+    network: Option<String>,
+    // unix seconds
+    time: u64,
+}
+
+impl IpLocation {
+    fn place(&self) -> Place {
+        Place {
+            latlon: LatLon {
+                lat: self.lat,
+                lon: self.lon,
+            },
+            timezone: self.timezone.clone(),
+        }
+    }
+}
+
+fn ip_path() -> Option<PathBuf> {
+    Some(state_dir()?.join("ip-location.json"))
+}
+
+// interface and gateway of the default route, which change when the machine joins another
+// network. None without /proc/net/route or without a default route
+fn network() -> Option<String> {
+    let routes = fs::read_to_string("/proc/net/route").ok()?;
+    routes
+        .lines()
+        .skip(1)
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        // Iface Destination Gateway Flags RefCnt Use Metric Mask ...
+        .filter(|f| f.len() > 7 && f[1] == "00000000" && f[7] == "00000000")
+        .min_by_key(|f| f[6].parse::<u32>().unwrap_or(u32::MAX))
+        .map(|f| format!("{} {}", f[0], f[2]))
+}
+
+fn save_ip(entry: &IpLocation) -> Result<()> {
+    let path = ip_path().ok_or_else(|| anyhow!("could not determine state directory"))?;
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(path, serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+// services that locate the address a request comes from
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum IpProvider {
+    // ip-api.com, plain http only
+    IpApi,
+    // ipinfo.io
+    Ipinfo,
+    // ifconfig.co, which can be self-hosted as echoip
+    IfconfigCo,
+    // any url answering with json that has lat/latitude and lon/lng/longitude or an ipinfo
+    // style "loc", and optionally timezone or time_zone
+    Url(String),
+}
+
+impl IpProvider {
+    // tried in this order when none are configured
+    pub const DEFAULT: &'static [IpProvider] = &[
+        IpProvider::IpApi,
+        IpProvider::Ipinfo,
+        IpProvider::IfconfigCo,
+    ];
+
+    pub fn name(&self) -> &str {
+        match self {
+            IpProvider::IpApi => "ip-api",
+            IpProvider::Ipinfo => "ipinfo",
+            IpProvider::IfconfigCo => "ifconfig-co",
+            IpProvider::Url(url) => url,
+        }
+    }
+
+    fn url(&self) -> &str {
+        match self {
+            IpProvider::IpApi => "http://ip-api.com/json/",
+            IpProvider::Ipinfo => "https://ipinfo.io/json",
+            IpProvider::IfconfigCo => "https://ifconfig.co/json",
+            IpProvider::Url(url) => url,
+        }
+    }
+
+    pub fn locate(&self) -> Result<Place> {
+        match self {
+            IpProvider::IpApi => {
+                let ip: IpApiResponse = request_checked(self.url(), IP_TIMEOUT)?;
+                if ip.status != "success" {
+                    return Err(anyhow!(
+                        "{}",
+                        ip.message
+                            .unwrap_or_else(|| format!("status {:?}", ip.status))
+                    ));
+                }
+                place(ip.lat, ip.lon, Some(ip.timezone))
+            }
+            IpProvider::Ipinfo => {
+                let ip: IpinfoResponse = request_checked(self.url(), IP_TIMEOUT)?;
+                let loc = ip.loc.ok_or_else(|| anyhow!("no location in response"))?;
+                let (lat, lon) = parse_loc(&loc)?;
+                place(lat, lon, ip.timezone)
+            }
+            IpProvider::IfconfigCo => {
+                let ip: IfconfigResponse = request_checked(self.url(), IP_TIMEOUT)?;
+                match (ip.latitude, ip.longitude) {
+                    (Some(lat), Some(lon)) => place(lat, lon, ip.time_zone),
+                    _ => Err(anyhow!("no location in response")),
+                }
+            }
+            IpProvider::Url(url) => {
+                let ip: serde_json::Value = request_checked(url, IP_TIMEOUT)?;
+                let field = |names: &[&str]| names.iter().find_map(|n| ip.get(*n)?.as_f64());
+                let (lat, lon) = match (
+                    field(&["lat", "latitude"]),
+                    field(&["lon", "lng", "longitude"]),
+                    ip.get("loc").and_then(|l| l.as_str()),
+                ) {
+                    (Some(lat), Some(lon), _) => (lat, lon),
+                    (_, _, Some(loc)) => parse_loc(loc)?,
+                    _ => return Err(anyhow!("no latitude and longitude in response")),
+                };
+                let timezone = ["timezone", "time_zone"]
+                    .iter()
+                    .find_map(|n| ip.get(*n)?.as_str())
+                    .map(str::to_string);
+                place(lat, lon, timezone)
+            }
+        }
+    }
+}
+
+impl FromStr for IpProvider {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "ip-api" => Ok(IpProvider::IpApi),
+            "ipinfo" => Ok(IpProvider::Ipinfo),
+            "ifconfig-co" | "ifconfig.co" => Ok(IpProvider::IfconfigCo),
+            url if url.starts_with("http://") || url.starts_with("https://") => {
+                Ok(IpProvider::Url(url.to_string()))
+            }
+            other => Err(anyhow!(
+                "unknown IP provider \"{other}\", expected ip-api, ipinfo, ifconfig-co or an http(s) URL"
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for IpProvider {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+// "41.8500,-87.6500"
+fn parse_loc(loc: &str) -> Result<(f64, f64)> {
+    let (lat, lon) = loc
+        .split_once(',')
+        .ok_or_else(|| anyhow!("malformed location {loc:?}"))?;
+    match (lat.trim().parse(), lon.trim().parse()) {
+        (Ok(lat), Ok(lon)) => Ok((lat, lon)),
+        _ => Err(anyhow!("malformed location {loc:?}")),
+    }
+}
+
+// the timezone is looked up offline when the provider doesn't name one
+fn place(lat: f64, lon: f64, timezone: Option<String>) -> Result<Place> {
+    let latlon = LatLon::new(lat, lon)?;
+    Ok(match timezone.filter(|tz| !tz.is_empty()) {
+        Some(timezone) => Place { latlon, timezone },
+        None => place_at(latlon),
+    })
+}
+
+// the first location any of `providers` gives, every failure when none does
+fn lookup_ip(providers: &[IpProvider]) -> Result<Place> {
+    let mut errors = vec![];
+    for provider in providers {
+        match provider.locate() {
+            Ok(place) => return Ok(place),
+            Err(e) => {
+                debug!("IP provider {} failed: {e}", provider.name());
+                errors.push(format!("  {}: {e}", provider.name()));
+            }
+        }
+    }
+    Err(anyhow!(
+        "could not locate this machine by its IP address\n{}",
+        errors.join("\n")
+    ))
+}
+
+// the approximate location of this machine's public ip address from the first of `providers`
+// that answers. it is only looked up again after IP_TTL, when the default route changed or
+// on `refresh`, and the last one stands in when every provider fails
+pub fn ip_place(providers: &[IpProvider], now: u64, refresh: bool) -> Result<Place> {
+    let network = network();
+    let cached: Option<IpLocation> = ip_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok());
+
+    if let Some(cached) = &cached {
+        if !refresh && cached.network == network && now.saturating_sub(cached.time) < IP_TTL {
+            debug!("IP location from cache.");
+            return Ok(cached.place());
+        }
+    }
+
+    match lookup_ip(providers) {
+        Ok(place) => {
+            let entry = IpLocation {
+                lat: place.latlon.lat,
+                lon: place.latlon.lon,
+                timezone: place.timezone.clone(),
+                network,
+                time: now,
+            };
+            if let Err(e) = save_ip(&entry) {
+                debug!("Err: {e}");
+            }
+            Ok(place)
+        }
+        Err(e) => match cached {
+            Some(cached) => {
+                debug!("IP lookup failed, using the last location: {e}");
+                Ok(cached.place())
+            }
+            None => Err(e),
+        },
+    }
+}
+
+fn names_path() -> Option<PathBuf> {
+    Some(state_dir()?.join("names.json"))
+}
+
+// places found near coordinates, so naming them doesn't read every downloaded place each run
+#[derive(Serialize, Deserialize, Default)]
+struct Names {
+    // geonames::version of the places these were found in
+    version: String,
+    names: BTreeMap<String, Option<(GeocodingResult, f64)>>,
+}
+
+// coordinates are rarely typed in many different ways, start over past this many
+const MAX_NAMES: usize = 64;
+
+// the downloaded GeoNames place nearest to `latlon` and its distance in km, if one is within
+// NEAR_KM
+pub fn name_at(latlon: LatLon) -> Option<(GeocodingResult, f64)> {
+    let version = geonames::version()?;
+    let key = format!("{:.5},{:.5}", latlon.lat, latlon.lon);
+    let mut names = names_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<Names>(&content).ok())
+        .filter(|names| names.version == version)
+        .unwrap_or(Names {
+            version,
+            names: BTreeMap::new(),
+        });
+    if let Some(found) = names.names.get(&key) {
+        return found.clone();
+    }
+
+    let cities = geonames::load()?;
+    let found = geonames::nearest(&cities, latlon, NEAR_KM).map(|(c, km)| (c.to_result(), km));
+    if names.names.len() >= MAX_NAMES {
+        names.names.clear();
+    }
+    names.names.insert(key, found.clone());
+    // only saves time next run, so failing to write is fine
+    if let Some(path) = names_path() {
+        let _ = fs::create_dir_all(path.parent().unwrap());
+        let _ = fs::write(path, serde_json::to_string(&names).unwrap_or_default());
+    }
+    found
+}
+
+// exact coordinates, the timezone is looked up offline
+pub fn place_at(latlon: LatLon) -> Place {
+    Place {
+        latlon,
+        timezone: tzf_rs::DefaultFinder::new()
+            .get_tz_name(latlon.lon, latlon.lat)
+            .to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_url_encoding() {
+        let opts = SearchOptions {
+            country: Some("br".to_string()),
+            lang: Some("pt&x=1".to_string()),
+            grid: None,
+        };
+        assert_eq!(
+            search_url("São Paulo #1 & co", &opts),
+            "https://geocoding-api.open-meteo.com/v1/search?name=S%C3%A3o%20Paulo%20%231%20%26%20co\
+             &count=10&language=pt%26x%3D1&format=json&countryCode=BR"
+        );
+        assert_eq!(
+            search_url("Chicago", &SearchOptions::default()),
+            "https://geocoding-api.open-meteo.com/v1/search?name=Chicago&count=10&language=en&format=json"
+        );
+        // a literal plus survives request_api's escaping of '+'
+        assert!(search_url("a+b", &SearchOptions::default()).contains("name=a%2Bb&"));
+    }
+}
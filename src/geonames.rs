@@ -0,0 +1,374 @@
+// an offline gazetteer from one of GeoNames' cities extracts (CC BY 4.0, geonames.org),
+// downloaded once and searched before the online geocoding api. it also names the place
+// nearest to coordinates
+use anyhow::{anyhow, Result};
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    env, fs,
+    io::{Cursor, Read},
+    path::PathBuf,
+    time::UNIX_EPOCH,
+};
+
+use crate::geo::{GeocodingResult, SearchOptions};
+use crate::{request_bytes, LatLon};
+
+pub const DEFAULT_URL: &str = "https://download.geonames.org/export/dump";
+
+// the extracts GeoNames offers, by the population a place needs to be in one
+pub const EXTRACTS: [u32; 4] = [500, 1000, 5000, 15000];
+
+// the smallest extract, around 30,000 places
+pub const DEFAULT_EXTRACT: u32 = 15000;
+
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+pub struct City {
+    pub name: String,
+    // lowercase ascii spelling, what searches are matched against
+    pub ascii: String,
+    pub latlon: LatLon,
+    pub country_code: String,
+    pub country: String,
+    pub admin1_code: String,
+    pub admin1: String,
+    pub population: u64,
+    pub timezone: String,
+}
+
+// $XDG_DATA_HOME/weather-rs/cities.tsv, falling back to ~/.local/share
+pub fn path() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".local/share"),
+    };
+    Some(base.join("weather-rs").join("cities.tsv"))
+}
+
+// changes whenever the downloaded places do, None until `download` was run
+pub fn version() -> Option<String> {
+    let meta = fs::metadata(path()?).ok()?;
+    let modified = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some(format!("{}-{}", meta.len(), modified.as_secs()))
+}
+
+// the downloaded places, None until `download` was run
+pub fn load() -> Option<Vec<City>> {
+    let content = fs::read_to_string(path()?).ok()?;
+    Some(
+        content
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(parse)
+            .collect(),
+    )
+}
+
+// name, ascii name, lat, lon, country code, country, admin1 code, admin1, population, timezone
+fn parse(line: &str) -> Option<City> {
+    let f: Vec<&str> = line.split('\t').collect();
+    if f.len() < 10 {
+        return None;
+    }
+    Some(City {
+        name: f[0].to_string(),
+        ascii: f[1].to_lowercase(),
+        latlon: LatLon::new(f[2].parse().ok()?, f[3].parse().ok()?).ok()?,
+        country_code: f[4].to_string(),
+        country: f[5].to_string(),
+        admin1_code: f[6].to_string(),
+        admin1: f[7].to_string(),
+        population: f[8].parse().unwrap_or(0),
+        timezone: f[9].to_string(),
+    })
+}
+
+// fetches the extract of places with at least `min_population` people from `base_url` along
+// with the names of countries and their subdivisions, and stores the columns searches need.
+// returns how many places there are
+pub fn download(base_url: &str, min_population: u32) -> Result<usize> {
+    if !EXTRACTS.contains(&min_population) {
+        return Err(anyhow!(
+            "GeoNames has no extract for {min_population}, only for {EXTRACTS:?}"
+        ));
+    }
+    let base_url = base_url.trim_end_matches('/');
+    let name = format!("cities{min_population}");
+
+    let archive = request_bytes(&format!("{base_url}/{name}.zip"))?;
+    let mut cities = String::new();
+    zip::ZipArchive::new(Cursor::new(archive))?
+        .by_name(&format!("{name}.txt"))?
+        .read_to_string(&mut cities)?;
+    let admin1 = String::from_utf8(request_bytes(&format!("{base_url}/admin1CodesASCII.txt"))?)?;
+    let countries = String::from_utf8(request_bytes(&format!("{base_url}/countryInfo.txt"))?)?;
+
+    // "US.IL" -> "Illinois"
+    let admin1: HashMap<&str, &str> = admin1
+        .lines()
+        .filter_map(|line| {
+            let f: Vec<&str> = line.split('\t').collect();
+            Some((*f.first()?, *f.get(1)?))
+        })
+        .collect();
+    // "US" -> "United States"
+    let countries: HashMap<&str, &str> = countries
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let f: Vec<&str> = line.split('\t').collect();
+            Some((*f.first()?, *f.get(4)?))
+        })
+        .collect();
+
+    let mut dst = format!("# GeoNames {name}, CC BY 4.0 geonames.org\n");
+    let mut count = 0;
+    // geonameid, name, asciiname, alternatenames, latitude, longitude, feature class,
+    // feature code, country code, cc2, admin1 code, ..., population, elevation, dem, timezone
+    for line in cities.lines() {
+        let f: Vec<&str> = line.split('\t').collect();
+        if f.len() < 18 {
+            continue;
+        }
+        let (country_code, admin1_code) = (f[8], f[10]);
+        let admin1_name = admin1
+            .get(format!("{country_code}.{admin1_code}").as_str())
+            .copied()
+            .unwrap_or_default();
+        let country = countries.get(country_code).copied().unwrap_or(country_code);
+        dst.push_str(
+            &[
+                f[1],
+                f[2],
+                f[4],
+                f[5],
+                country_code,
+                country,
+                admin1_code,
+                admin1_name,
+                f[14],
+                f[17],
+            ]
+            .join("\t"),
+        );
+        dst.push('\n');
+        count += 1;
+    }
+
+    let path = path().ok_or_else(|| anyhow!("could not determine data directory"))?;
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(&path, dst)?;
+    Ok(count)
+}
+
+// deletes the downloaded places, returning whether there were any
+pub fn remove() -> Result<bool> {
+    match path() {
+        Some(path) if path.exists() => {
+            fs::remove_file(path)?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+impl City {
+    // "Springfield, IL" and "Springfield, Illinois, US" narrow the search by what follows
+    // the name
+    fn qualified_by(&self, qualifier: &str) -> bool {
+        let q = qualifier.to_lowercase();
+        [
+            &self.admin1_code,
+            &self.admin1,
+            &self.country_code,
+            &self.country,
+        ]
+        .iter()
+        .any(|field| !field.is_empty() && field.to_lowercase() == q)
+    }
+
+    pub fn to_result(&self) -> GeocodingResult {
+        GeocodingResult {
+            name: self.name.clone(),
+            latitude: self.latlon.lat,
+            longitude: self.latlon.lon,
+            timezone: self.timezone.clone(),
+            country: Some(self.country.clone()),
+            admin1: Some(self.admin1.clone()).filter(|a| !a.is_empty()),
+            country_code: Some(self.country_code.clone()),
+            population: Some(self.population),
+        }
+    }
+}
+
+// how closely a name has to match: exactly, by its beginning, or a typo or two away
+// depending on its length
+fn rank(city: &City, query: &str) -> Option<u8> {
+    let name = city.name.to_lowercase();
+    if name == query || city.ascii == query {
+        return Some(0);
+    }
+    if query.len() >= 3 && (name.starts_with(query) || city.ascii.starts_with(query)) {
+        return Some(1);
+    }
+    let typos = match query.chars().count() {
+        0..=3 => return None,
+        4..=7 => 1,
+        _ => 2,
+    };
+    if city.ascii.len().abs_diff(query.len()) > typos {
+        return None;
+    }
+    (strsim::damerau_levenshtein(&city.ascii, query) <= typos).then_some(2)
+}
+
+// places matching a name like "Springfield" or "Springfield, IL", the closest matches first
+// and the most populous first among equally close ones
+pub fn search(cities: &[City], query: &str, opts: &SearchOptions) -> Vec<GeocodingResult> {
+    let mut parts = query.split(',').map(str::trim);
+    let name = parts.next().unwrap_or_default().to_lowercase();
+    let qualifiers: Vec<&str> = parts.filter(|q| !q.is_empty()).collect();
+
+    let mut matches: Vec<(u8, &City)> = cities
+        .iter()
+        .filter(|c| {
+            opts.country
+                .as_ref()
+                .is_none_or(|cc| c.country_code.eq_ignore_ascii_case(cc))
+        })
+        .filter(|c| qualifiers.iter().all(|q| c.qualified_by(q)))
+        .filter_map(|c| Some((rank(c, &name)?, c)))
+        .collect();
+    matches.sort_by_key(|(rank, c)| (*rank, Reverse(c.population)));
+    matches.into_iter().map(|(_, c)| c.to_result()).collect()
+}
+
+// great circle distance
+fn distance_km(a: LatLon, b: LatLon) -> f64 {
+    let (lat_a, lat_b) = (a.lat.to_radians(), b.lat.to_radians());
+    let d_lat = lat_b - lat_a;
+    let d_lon = (b.lon - a.lon).to_radians();
+    let h = (d_lat / 2.0).sin().powi(2) + lat_a.cos() * lat_b.cos() * (d_lon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * h.sqrt().asin()
+}
+
+// the place closest to `latlon` and how far away it is, if one is within `max_km`
+pub fn nearest(cities: &[City], latlon: LatLon, max_km: f64) -> Option<(&City, f64)> {
+    cities
+        .iter()
+        .map(|c| (c, distance_km(c.latlon, latlon)))
+        .filter(|(_, km)| *km <= max_km)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLACES: &str = "\
+Springfield\tSpringfield\t39.80172\t-89.64371\tUS\tUnited States\tIL\tIllinois\t114394\tAmerica/Chicago
+Springfield\tSpringfield\t37.21533\t-93.29824\tUS\tUnited States\tMO\tMissouri\t169176\tAmerica/Chicago
+Springfield\tSpringfield\t42.10148\t-72.58981\tUS\tUnited States\tMA\tMassachusetts\t155929\tAmerica/New_York
+Paris\tParis\t48.85341\t2.3488\tFR\tFrance\t11\tÎle-de-France\t2138551\tEurope/Paris
+Paris\tParis\t33.66094\t-95.55551\tUS\tUnited States\tTX\tTexas\t24782\tAmerica/Chicago
+München\tMunchen\t48.13743\t11.57549\tDE\tGermany\t02\tBavaria\t1260391\tEurope/Berlin
+Chicago\tChicago\t41.85003\t-87.65005\tUS\tUnited States\tIL\tIllinois\t2720546\tAmerica/Chicago
+Cicero\tCicero\t41.84559\t-87.75394\tUS\tUnited States\tIL\tIllinois\t83891\tAmerica/Chicago";
+
+    fn cities() -> Vec<City> {
+        PLACES.lines().filter_map(parse).collect()
+    }
+
+    fn find(name: &str) -> City {
+        cities().into_iter().find(|c| c.name == name).unwrap()
+    }
+
+    // (name, region) of the matches for `query`
+    fn found(query: &str, opts: &SearchOptions) -> Vec<(String, String)> {
+        search(&cities(), query, opts)
+            .into_iter()
+            .map(|r| (r.name, r.admin1.unwrap_or_default()))
+            .collect()
+    }
+
+    #[test]
+    fn ranks() {
+        let munich = find("München");
+        // exact, by either spelling
+        assert_eq!(rank(&munich, "münchen"), Some(0));
+        assert_eq!(rank(&munich, "munchen"), Some(0));
+        // by the beginning, from three letters on
+        assert_eq!(rank(&munich, "mün"), Some(1));
+        assert_eq!(rank(&munich, "mu"), None);
+        // one typo for short names, two for long ones
+        assert_eq!(rank(&munich, "muncen"), Some(2));
+        assert_eq!(rank(&munich, "mnuchen"), Some(2));
+        assert_eq!(rank(&munich, "mnucen"), None);
+        let springfield = find("Springfield");
+        assert_eq!(rank(&springfield, "sprnigfeld"), Some(2));
+        assert_eq!(rank(&springfield, "sprnigfed"), None);
+        // short queries only match exactly or by their beginning
+        assert_eq!(rank(&find("Paris"), "pari"), Some(1));
+        assert_eq!(rank(&find("Paris"), "parls"), Some(2));
+        assert_eq!(rank(&find("Paris"), "pxr"), None);
+    }
+
+    #[test]
+    fn search_order() {
+        let all = SearchOptions::default();
+        // the most populous of equally close matches first
+        assert_eq!(
+            found("springfield", &all),
+            [
+                ("Springfield".to_string(), "Missouri".to_string()),
+                ("Springfield".to_string(), "Massachusetts".to_string()),
+                ("Springfield".to_string(), "Illinois".to_string()),
+            ]
+        );
+        // closer matches before bigger places
+        let names: Vec<String> = found("chic", &all).into_iter().map(|(n, _)| n).collect();
+        assert_eq!(names, ["Chicago"]);
+        assert_eq!(found("Cicero", &all)[0].0, "Cicero");
+        assert!(found("nowhere", &all).is_empty());
+    }
+
+    #[test]
+    fn search_qualifiers() {
+        let all = SearchOptions::default();
+        // by state code or name, country code or name, ignoring case
+        assert_eq!(found("Springfield, IL", &all)[0].1, "Illinois");
+        assert_eq!(found("springfield, missouri", &all)[0].1, "Missouri");
+        assert_eq!(found("Springfield, Massachusetts, US", &all).len(), 1);
+        assert_eq!(
+            found("Paris, FR", &all),
+            [("Paris".to_string(), "Île-de-France".to_string())]
+        );
+        assert_eq!(found("Paris, united states", &all)[0].1, "Texas");
+        assert!(found("Springfield, FR", &all).is_empty());
+        // trailing commas are no qualifier
+        assert_eq!(found("Paris,", &all).len(), 2);
+
+        let us = SearchOptions {
+            country: Some("us".to_string()),
+            ..SearchOptions::default()
+        };
+        assert_eq!(found("Paris", &us)[0].1, "Texas");
+    }
+
+    #[test]
+    fn nearest_place() {
+        let cities = cities();
+        // the loop in Chicago, Cicero is 10km further west
+        let (city, km) = nearest(&cities, LatLon::new(41.8781, -87.6298).unwrap(), 50.0).unwrap();
+        assert_eq!(city.name, "Chicago");
+        assert!((km - 3.5).abs() < 0.5, "{km}");
+        let (city, _) = nearest(&cities, LatLon::new(41.85, -87.76).unwrap(), 50.0).unwrap();
+        assert_eq!(city.name, "Cicero");
+        // nothing within reach
+        assert!(nearest(&cities, LatLon::new(0.0, 0.0).unwrap(), 50.0).is_none());
+        // Paris to Munich is about 685km
+        let km = distance_km(find("Paris").latlon, find("München").latlon);
+        assert!((km - 685.0).abs() < 5.0, "{km}");
+    }
+}
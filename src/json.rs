@@ -0,0 +1,273 @@
+// machine readable output for `--format json`
+// bump SCHEMA_VERSION whenever a field is renamed, removed or changes meaning
+use serde::Serialize;
+
+use crate::forecast::{Forecast, Stats};
+use crate::moon::Moon;
+use crate::render::{get_time_index, stale_minutes, wind_di_decode};
+use crate::wmo::{self, Severity, WmoCode};
+use crate::{compute_wet_bulb, Mode, RenderOptions, TempScale, Window};
+
+const SCHEMA_VERSION: u32 = 2;
+
+#[derive(Serialize)]
+struct Output {
+    schema_version: u32,
+    mode: &'static str,
+    generated_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stale_minutes: Option<u64>,
+    location: Location,
+    units: Units,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    current: Option<Current>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hourly: Option<Vec<Sample>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    daily: Option<Vec<Day>>,
+}
+
+#[derive(Serialize)]
+struct Location {
+    latitude: f64,
+    longitude: f64,
+    elevation: f64,
+    timezone: String,
+    timezone_abbreviation: String,
+    utc_offset_seconds: i64,
+}
+
+#[derive(Serialize)]
+struct Units {
+    time: &'static str,
+    temperature: String,
+    relative_humidity: String,
+    precipitation_probability: String,
+    wind_speed: String,
+    wind_direction: String,
+    precipitation: String,
+    pressure: String,
+}
+
+#[derive(Serialize)]
+struct Wind {
+    speed: f64,
+    direction: i16,
+    compass: &'static str,
+}
+
+#[derive(Serialize)]
+struct Weather {
+    code: u8,
+    text: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    severity: Option<Severity>,
+}
+
+#[derive(Serialize)]
+struct Current {
+    time: u32,
+    temperature: f64,
+    relative_humidity: f64,
+    wet_bulb: f64,
+    wind: Wind,
+    weather: Weather,
+    is_day: bool,
+    moon_phase: &'static str,
+    moon_illumination: f64,
+    precipitation_probability_max: i32,
+    sunrise: u32,
+    sunset: u32,
+}
+
+#[derive(Serialize)]
+struct Sample {
+    time: u32,
+    temperature: f64,
+    relative_humidity: f64,
+    wet_bulb: f64,
+    precipitation_probability: f64,
+    // absent in forecasts cached before they were fetched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    precipitation: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pressure: Option<f64>,
+    wind: Wind,
+    weather: Weather,
+    is_day: bool,
+    moon_phase: &'static str,
+}
+
+#[derive(Serialize)]
+struct Day {
+    date: String,
+    time: u32,
+    temperature: Stats,
+    relative_humidity: Stats,
+    // only for days aggregated from hourly samples
+    #[serde(skip_serializing_if = "Option::is_none")]
+    wet_bulb: Option<Stats>,
+    wind_speed: Stats,
+    precipitation_probability_max: i32,
+    uv_index_max: f64,
+    weather: Weather,
+    sunrise: u32,
+    sunset: u32,
+    moon_phase: &'static str,
+}
+
+// `celsius` in `scale`, without the float noise converting leaves
+fn temp(scale: TempScale, celsius: f64) -> f64 {
+    (scale.from_celsius(celsius) * 100.0).round() / 100.0
+}
+
+fn weather(code: u8) -> Weather {
+    Weather {
+        code,
+        text: wmo::text(code),
+        severity: WmoCode::from_code(code).map(WmoCode::severity),
+    }
+}
+
+fn wind(speed: f64, direction: i16) -> Wind {
+    Wind {
+        speed,
+        direction,
+        compass: wind_di_decode(direction),
+    }
+}
+
+fn current(md: &Forecast, scale: TempScale, now: u64) -> Current {
+    let m = &md.minutely_15;
+    let now = get_time_index(&m.time, now);
+    let today = md.day_index(m.time[now]);
+    let (sunrise, sunset) = (md.daily.sunrise[today], md.daily.sunset[today]);
+    let moon = Moon::at(m.time[now]);
+
+    Current {
+        time: m.time[now],
+        temperature: temp(scale, m.temperature_2m[now]),
+        relative_humidity: m.relative_humidity_2m[now],
+        wet_bulb: temp(
+            scale,
+            compute_wet_bulb(m.temperature_2m[now], m.relative_humidity_2m[now]),
+        ),
+        wind: wind(m.wind_speed_10m[now], m.wind_direction_10m[now]),
+        weather: weather(m.weather_code[now]),
+        is_day: m.time[now] < sunset && m.time[now] > sunrise,
+        moon_phase: moon.phase.name(),
+        moon_illumination: moon.illumination,
+        precipitation_probability_max: md.daily.precipitation_probability_max[today],
+        sunrise,
+        sunset,
+    }
+}
+
+// the same window the hourly table shows, at full resolution
+fn hourly(md: &Forecast, scale: TempScale, window: &Window, now: u64) -> Vec<Sample> {
+    let m = &md.minutely_15;
+    let now = get_time_index(&m.time, now);
+    let today = md.day_index(m.time[now]);
+    let (sunrise, sunset) = (md.daily.sunrise[today], md.daily.sunset[today]);
+
+    window
+        .sample_range(now, m.time.len())
+        .map(|i| Sample {
+            time: m.time[i],
+            temperature: temp(scale, m.temperature_2m[i]),
+            relative_humidity: m.relative_humidity_2m[i],
+            wet_bulb: temp(
+                scale,
+                compute_wet_bulb(m.temperature_2m[i], m.relative_humidity_2m[i]),
+            ),
+            precipitation_probability: m.precipitation_probability[i],
+            precipitation: m.precipitation.get(i).copied(),
+            pressure: md.pressure_at(m.time[i]),
+            wind: wind(m.wind_speed_10m[i], m.wind_direction_10m[i]),
+            weather: weather(m.weather_code[i]),
+            is_day: m.time[i] < sunset && m.time[i] > sunrise,
+            moon_phase: Moon::at(m.time[i]).phase.name(),
+        })
+        .collect()
+}
+
+// the days the weekly table shows
+fn daily(md: &Forecast, scale: TempScale, window: &Window, now: u64) -> Vec<Day> {
+    let d = &md.daily;
+    let days = md.days(now as u32);
+    let shown = window.day_range(md.day_index(now as u32), days.len());
+
+    days.into_iter()
+        .enumerate()
+        .skip(shown.start)
+        .take(shown.len())
+        .map(|(i, day)| {
+            let date = md.local_time(d.time[i]).format("%Y-%m-%d").to_string();
+            Day {
+                date,
+                time: d.time[i],
+                temperature: day.temperature.map(|t| temp(scale, t)),
+                relative_humidity: day.relative_humidity,
+                wet_bulb: day.wet_bulb.map(|wb| wb.map(|t| temp(scale, t))),
+                wind_speed: day.wind_speed,
+                precipitation_probability_max: d.precipitation_probability_max[i],
+                uv_index_max: d.uv_index_max[i],
+                weather: weather(d.weather_code[i]),
+                sunrise: d.sunrise[i],
+                sunset: d.sunset[i],
+                moon_phase: Moon::at(d.time[i] + 12 * 3600).phase.name(),
+            }
+        })
+        .collect()
+}
+
+// the data for the given mode as a single JSON document
+pub fn render(md: &Forecast, mode: Mode, opts: &RenderOptions, now: u64) -> String {
+    let scale = opts.temperature.scale;
+    let units = Units {
+        time: "unixtime",
+        temperature: scale.label().to_string(),
+        relative_humidity: md.units.relative_humidity.clone(),
+        precipitation_probability: md.units.precipitation_probability.clone(),
+        wind_speed: md.units.wind_speed.clone(),
+        wind_direction: md.units.wind_direction.clone(),
+        precipitation: md.units.precipitation.clone(),
+        pressure: md.units.pressure.clone(),
+    };
+    let location = Location {
+        latitude: md.latitude,
+        longitude: md.longitude,
+        elevation: md.elevation,
+        timezone: md.timezone.clone(),
+        timezone_abbreviation: md.timezone_abbreviation.clone(),
+        utc_offset_seconds: md.utc_offset_seconds,
+    };
+
+    let mut output = Output {
+        schema_version: SCHEMA_VERSION,
+        mode: "",
+        generated_at: now,
+        stale_minutes: stale_minutes(md, now),
+        location,
+        units,
+        current: None,
+        hourly: None,
+        daily: None,
+    };
+    match mode {
+        Mode::Current => {
+            output.mode = "current";
+            output.current = Some(current(md, scale, now));
+        }
+        Mode::Hourly => {
+            output.mode = "hourly";
+            output.hourly = Some(hourly(md, scale, &opts.window, now));
+        }
+        Mode::Daily => {
+            output.mode = "daily";
+            output.daily = Some(daily(md, scale, &opts.window, now));
+        }
+    }
+
+    format!("{}\n", serde_json::to_string(&output).unwrap())
+}
@@ -0,0 +1,690 @@
+// weather-rs as a library: locating, fetching and caching forecasts and rendering them
+// as tables, one line summaries, JSON or status bar output
+//
+// nothing here reads the command line or the clock directly, everything comes in through
+// FetchOptions, RenderOptions and a Clock so other tools can drive it
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+use std::{
+    ops::Range,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+// ahead of the modules so they can use it too
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        if $crate::DEBUG.load(std::sync::atomic::Ordering::Relaxed) {
+            println!($($arg)*);
+        }
+    };
+}
+
+pub mod bar;
+pub mod cache;
+pub mod color;
+pub mod column;
+pub mod coords;
+pub mod forecast;
+pub mod geo;
+pub mod geonames;
+pub mod json;
+pub mod moon;
+pub mod provider;
+pub mod render;
+mod structs;
+pub mod template;
+pub mod units;
+pub mod width;
+pub mod wmo;
+
+use column::Column;
+use forecast::Forecast;
+use provider::{Place, WeatherProvider};
+use template::Template;
+use units::{TempDisplay, UnitSystem};
+
+#[derive(Clone, Debug)]
+pub enum MyError {
+    InvalidLatitude(f64),
+    InvalidLongitude(f64),
+    // coordinates in none of the formats coords::parse knows
+    UnknownFormat,
+    // coordinates that look like `format` but aren't valid in it
+    Malformed {
+        format: &'static str,
+        reason: String,
+    },
+}
+
+impl MyError {
+    pub(crate) fn malformed(format: &'static str, reason: impl Into<String>) -> Self {
+        MyError::Malformed {
+            format,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for MyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MyError::InvalidLatitude(lat) => write!(f, "latitude {lat} is outside -90 to 90"),
+            MyError::InvalidLongitude(lon) => write!(f, "longitude {lon} is outside -180 to 180"),
+            MyError::UnknownFormat => write!(
+                f,
+                "not a known coordinate format, expected \"lat,lon\", degrees-minutes-seconds, \
+                 a geo: URI, geohash, plus code, Maidenhead locator or UTM"
+            ),
+            MyError::Malformed { format, reason } => write!(f, "invalid {format}: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for MyError {}
+
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+pub enum Mode {
+    Current,
+    Hourly,
+    Daily,
+}
+
+#[derive(Clone, Copy, Debug, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Format {
+    Text,
+    Json,
+    Waybar,
+    I3blocks,
+    Polybar,
+}
+
+#[derive(Clone, Copy, Debug, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Provider {
+    OpenMeteo,
+    MetNorway,
+}
+
+impl Provider {
+    pub fn backend(&self) -> &'static dyn WeatherProvider {
+        match self {
+            Provider::OpenMeteo => &provider::OpenMeteo,
+            Provider::MetNorway => &provider::MetNorway,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EmojiMode {
+    NerdFont,
+    Original,
+    Technical,
+}
+
+// how wide a character followed by VS16 is drawn, e.g. "☁️". wide is what Unicode
+// says, narrow is what terminals that ignore the selector do
+#[derive(Clone, Copy, Debug, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Vs16 {
+    Wide,
+    Narrow,
+}
+
+impl Vs16 {
+    // the width of a char of width `base` followed by VS16
+    pub fn width(&self, base: usize) -> usize {
+        match self {
+            Vs16::Wide => base.max(2),
+            Vs16::Narrow => base,
+        }
+    }
+}
+
+// forecasts always hold °C, the scale only matters when values are shown
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum, serde::Deserialize, serde::Serialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum TempScale {
+    #[value(alias = "f")]
+    #[serde(alias = "f")]
+    Fahrenheit,
+    #[value(alias = "c")]
+    #[serde(alias = "c")]
+    Celsius,
+    #[value(alias = "k")]
+    #[serde(alias = "k")]
+    Kelvin,
+}
+
+impl TempScale {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TempScale::Fahrenheit => "°F",
+            TempScale::Celsius => "°C",
+            TempScale::Kelvin => "K",
+        }
+    }
+
+    // what follows a value in the tables, kelvin isn't measured in degrees
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            TempScale::Kelvin => "K",
+            _ => "°",
+        }
+    }
+
+    pub fn from_celsius(&self, celsius: f64) -> f64 {
+        match self {
+            TempScale::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+            TempScale::Celsius => celsius,
+            TempScale::Kelvin => celsius + 273.15,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Copy)]
+pub struct LatLon {
+    // range: -90 to +90
+    pub lat: f64,
+    // range: -180 to +180
+    pub lon: f64,
+}
+
+impl LatLon {
+    pub fn new(lat: f64, lon: f64) -> Result<Self, MyError> {
+        match (lat, lon) {
+            (lat, _) if !(-90.0..=90.0).contains(&lat) => Err(MyError::InvalidLatitude(lat)),
+            (_, lon) if !(-180.0..=180.0).contains(&lon) => Err(MyError::InvalidLongitude(lon)),
+            (lat, lon) => Ok(Self { lat, lon }),
+        }
+    }
+
+    // the nearest point on a grid of `grid` degrees, so requests give away no more than that
+    pub fn snap(self, grid: Option<f64>) -> LatLon {
+        let Some(grid) = grid else {
+            return self;
+        };
+        // drops the float noise so urls show 41.9 rather than 41.900000000000006
+        let snap = |v: f64| ((v / grid).round() * grid * 1e6).round() / 1e6;
+        LatLon {
+            lat: snap(self.lat).clamp(-90.0, 90.0),
+            lon: snap(self.lon).clamp(-180.0, 180.0),
+        }
+    }
+}
+
+// source of the current time in unix seconds
+pub trait Clock {
+    fn now(&self) -> u64;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Wall time before unix epoch")
+            .as_secs()
+    }
+}
+
+// always the same time, for replaying cached forecasts or rendering a moment in the past
+pub struct FixedClock(pub u64);
+
+impl Clock for FixedClock {
+    fn now(&self) -> u64 {
+        self.0
+    }
+}
+
+// seconds a cached forecast is used before fetching a new one
+pub const CACHE_TIMEOUT: u64 = 1800;
+
+// the parts of a forecast a view reads beyond the current conditions and daily summary,
+// providers that can leave the others out of a request do
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SeriesSet {
+    pub minutely_15: bool,
+    pub hourly: bool,
+    // daily means and minimums for the days past forecast::HOURLY_DAYS
+    pub daily_stats: bool,
+}
+
+impl SeriesSet {
+    pub const ALL: SeriesSet = SeriesSet {
+        minutely_15: true,
+        hourly: true,
+        daily_stats: true,
+    };
+
+    // part of the cache key, so views needing different series don't overwrite each other's
+    // forecasts: "q" for the quarter hours, "h" for hours and "d" for daily aggregates
+    pub fn key(&self) -> String {
+        [
+            (self.minutely_15, "q"),
+            (self.hourly, "h"),
+            (self.daily_stats, "d"),
+        ]
+        .iter()
+        .filter(|(fetched, _)| *fetched)
+        .map(|(_, key)| *key)
+        .collect()
+    }
+}
+
+impl Mode {
+    // what showing this mode in `format` reads
+    pub fn series(&self, format: Format) -> SeriesSet {
+        match (format, self) {
+            // status bars show the current conditions and next hours whatever the mode
+            (Format::Waybar | Format::I3blocks | Format::Polybar, _) | (_, Mode::Current) => {
+                SeriesSet {
+                    minutely_15: true,
+                    hourly: false,
+                    daily_stats: false,
+                }
+            }
+            // hourly holds the pressure column
+            (_, Mode::Hourly) => SeriesSet {
+                minutely_15: true,
+                hourly: true,
+                daily_stats: false,
+            },
+            (_, Mode::Daily) => SeriesSet {
+                minutely_15: false,
+                hourly: true,
+                daily_stats: true,
+            },
+        }
+    }
+}
+
+// how much of the past and future to fetch and show
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Window {
+    // whole days before today and days from today on, the weekly view
+    pub past_days: u32,
+    pub days: u32,
+    // around the current sample, the hourly view
+    pub hours_back: u32,
+    pub hours_ahead: u32,
+}
+
+impl Default for Window {
+    fn default() -> Self {
+        Window {
+            past_days: 2,
+            days: 14,
+            hours_back: 6,
+            hours_ahead: 24,
+        }
+    }
+}
+
+impl Window {
+    // what open-meteo serves, other providers may have less
+    pub const MAX_PAST_DAYS: u32 = 92;
+    pub const MAX_DAYS: u32 = 16;
+
+    // the limits of each field, for the command line and the config file alike
+    pub fn check_past_days(past_days: u32) -> Result<u32, String> {
+        match past_days <= Window::MAX_PAST_DAYS {
+            true => Ok(past_days),
+            false => Err(format!(
+                "{past_days} is more than the {} days open-meteo keeps",
+                Window::MAX_PAST_DAYS
+            )),
+        }
+    }
+
+    pub fn check_days(days: u32) -> Result<u32, String> {
+        match (1..=Window::MAX_DAYS).contains(&days) {
+            true => Ok(days),
+            false => Err(format!(
+                "{days} is outside open-meteo's 1 to {}",
+                Window::MAX_DAYS
+            )),
+        }
+    }
+
+    pub fn check_hours_back(hours: u32) -> Result<u32, String> {
+        match hours <= (Window::MAX_PAST_DAYS + 1) * 24 {
+            true => Ok(hours),
+            false => Err(format!("{hours} is beyond any history")),
+        }
+    }
+
+    pub fn check_hours_ahead(hours: u32) -> Result<u32, String> {
+        match hours <= Window::MAX_DAYS * 24 {
+            true => Ok(hours),
+            false => Err(format!("{hours} is beyond any forecast")),
+        }
+    }
+
+    // the entries of `daily` to show, `today` indexes it
+    pub fn day_range(&self, today: usize, len: usize) -> Range<usize> {
+        let start = today.saturating_sub(self.past_days as usize);
+        start.min(len)..(today + self.days as usize).min(len)
+    }
+
+    // the minutely_15 samples to show around `now`, an index into them
+    pub fn sample_range(&self, now: usize, len: usize) -> Range<usize> {
+        let start = now.saturating_sub(self.hours_back as usize * 4);
+        start.min(len)..(now + self.hours_ahead as usize * 4).min(len)
+    }
+
+    // part of the cache key, a forecast only serves the window it was fetched for
+    pub fn key(&self) -> String {
+        format!("{}-{}", self.past_days, self.days)
+    }
+}
+
+// what to fetch and how hard to try
+#[derive(Clone, Debug)]
+pub struct FetchOptions {
+    pub provider: Provider,
+    pub units: UnitSystem,
+    pub series: SeriesSet,
+    pub window: Window,
+    // never use the network, serve the cache however old
+    pub offline: bool,
+    // skip the cache even when it is fresh
+    pub refresh: bool,
+    pub cache_timeout: u64,
+    // degrees coordinates are rounded to before they are sent anywhere
+    pub grid: Option<f64>,
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        FetchOptions {
+            provider: Provider::OpenMeteo,
+            units: UnitSystem::default(),
+            series: SeriesSet::ALL,
+            window: Window::default(),
+            offline: false,
+            refresh: false,
+            cache_timeout: CACHE_TIMEOUT,
+            grid: None,
+        }
+    }
+}
+
+impl FetchOptions {
+    // what besides place and provider tells cached forecasts apart
+    pub fn cache_key(&self) -> String {
+        format!(
+            "{}_{}_{}",
+            self.units.key(),
+            self.window.key(),
+            self.series.key()
+        )
+    }
+}
+
+// how to present a forecast
+#[derive(Clone, Debug)]
+pub struct RenderOptions {
+    pub emoji: EmojiMode,
+    pub vs16: Vs16,
+    pub color: bool,
+    pub temperature: TempDisplay,
+    pub window: Window,
+    // (columns, rows) the tables are fitted into
+    pub term_size: (usize, usize),
+    // replaces the default one line summary and status bar text
+    pub template: Option<Template>,
+    // hourly table columns, in order
+    pub columns: Vec<Column>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            emoji: EmojiMode::Technical,
+            vs16: Vs16::Wide,
+            color: true,
+            temperature: TempDisplay::default(),
+            window: Window::default(),
+            term_size: (80, 32),
+            template: None,
+            columns: Column::DEFAULT.to_vec(),
+        }
+    }
+}
+
+static DEBUG: AtomicBool = AtomicBool::new(false);
+
+// print what the library is doing to stdout, off by default
+pub fn set_debug(enabled: bool) {
+    DEBUG.store(enabled, Ordering::Relaxed);
+}
+
+// api.met.no rejects requests without an identifying user agent
+const USER_AGENT: &str = concat!(
+    "weather-rs/",
+    env!("CARGO_PKG_VERSION"),
+    " github.com/chellipse/weather-rs"
+);
+
+// request data from a website
+#[tokio::main]
+async fn request_api<T: DeserializeOwned>(text: &str) -> Result<T> {
+    let url = match text.split_once('?') {
+        Some((base, query)) => {
+            format!("{base}?{}", query.replace('+', "%2B"))
+        }
+        None => text.to_string(),
+    };
+    debug!("Querying {url:?}");
+
+    let client = reqwest::Client::builder().user_agent(USER_AGENT).build()?;
+    let body = client.get(url).send().await?.text().await?;
+
+    serde_json::from_str::<T>(&body).map_err(|e| anyhow!("{e:?} from {body:?}"))
+}
+
+// like request_api, but giving up after `timeout` and failing on error statuses
+#[tokio::main]
+async fn request_checked<T: DeserializeOwned>(url: &str, timeout: Duration) -> Result<T> {
+    debug!("Querying {url:?}");
+
+    let client = reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .timeout(timeout)
+        .build()?;
+    let response = client.get(url).send().await?;
+    let status = response.status();
+    let body = response.text().await?;
+    if !status.is_success() {
+        return Err(anyhow!("{status}"));
+    }
+
+    serde_json::from_str::<T>(&body).map_err(|e| anyhow!("{e} from {body:?}"))
+}
+
+// the body of `url` as is, failing on error statuses
+#[tokio::main]
+async fn request_bytes(url: &str) -> Result<Vec<u8>> {
+    debug!("Downloading {url:?}");
+
+    let client = reqwest::Client::builder().user_agent(USER_AGENT).build()?;
+    let response = client.get(url).send().await?.error_for_status()?;
+    Ok(response.bytes().await?.to_vec())
+}
+
+// Stull's wet bulb temperature in °C from °C and relative humidity in %
+pub fn compute_wet_bulb(temp: f64, rh: f64) -> f64 {
+    temp * (0.151977f64 * (rh + 8.313659f64).powf(1.0 / 2.0)).atan() + (temp + rh).atan()
+        - (rh - 1.676331f64).atan()
+        + 0.00391838f64 * rh.powf(3.0 / 2.0) * (0.023101f64 * rh).atan()
+        - 4.686035f64
+}
+
+// a new forecast straight from the provider, bypassing the cache
+pub fn fetch(place: &Place, opts: &FetchOptions, clock: &dyn Clock) -> Result<Forecast> {
+    if opts.offline {
+        return Err(anyhow!("Offline mode"));
+    }
+    opts.provider.backend().fetch(place, opts, clock.now())
+}
+
+// the forecast for `place` from the cache while it is fresh and from the provider otherwise,
+// falling back to the last cached forecast however old when fetching fails. `place` carries
+// the reason when the location couldn't be determined, the newest entry is used then
+pub fn forecast(place: Result<Place>, opts: &FetchOptions, clock: &dyn Clock) -> Result<Forecast> {
+    let now = clock.now();
+    let provider = opts.provider.backend().name();
+    let place = place.map(|p| Place {
+        latlon: p.latlon.snap(opts.grid),
+        ..p
+    });
+    let latlon = place.as_ref().ok().map(|p| p.latlon);
+
+    // only offline is the most recent forecast the best guess at where we are, otherwise
+    // weather for some other place would pass for ours
+    let cache_path = match latlon {
+        Some(l) => Some(cache::entry_path(l, provider, &opts.cache_key())),
+        None if opts.offline => cache::newest(provider, &opts.cache_key()),
+        None => None,
+    };
+    debug!("Cache entry: {cache_path:?}");
+
+    let cached = match &cache_path {
+        _ if opts.refresh => Err(anyhow!("Refresh requested")),
+        Some(path) => cache::load(path, opts, latlon, opts.cache_timeout, now),
+        None => Err(anyhow!("Location unknown")),
+    };
+    let error = match (cached, place) {
+        (Ok(md), _) => return Ok(md),
+        (Err(e), Ok(place)) => {
+            debug!("Cache fail: {e}");
+            match fetch(&place, opts, clock) {
+                Ok(md) => {
+                    debug!("Data received.");
+                    if let Some(path) = &cache_path {
+                        match cache::save(path, &md) {
+                            Ok(_) => debug!("Cache saved."),
+                            Err(e) => debug!("Err: {e}"),
+                        }
+                    }
+                    return Ok(md);
+                }
+                Err(e) => e,
+            }
+        }
+        (Err(e), Err(place_e)) => {
+            debug!("Cache fail: {e}");
+            place_e
+        }
+    };
+
+    // serves the last good response for the location when no fresh data can be had
+    match cache_path.map(|path| cache::load(&path, opts, latlon, u64::MAX, now)) {
+        Some(Ok(md)) => {
+            debug!("Err: {error}");
+            debug!("Serving stale cache.");
+            Ok(md)
+        }
+        _ => Err(error),
+    }
+}
+
+// the forecast in the given format, `mode` picks the view for text and json
+pub fn render(
+    md: &Forecast,
+    format: Format,
+    mode: Mode,
+    opts: &RenderOptions,
+    clock: &dyn Clock,
+) -> String {
+    let now = clock.now();
+    match (format, mode) {
+        (Format::Json, _) => json::render(md, mode, opts, now),
+        (Format::Waybar, _) => bar::waybar(md, opts, now),
+        (Format::I3blocks, _) => bar::i3blocks(md, opts, now),
+        (Format::Polybar, _) => bar::polybar(md, opts, now),
+        (Format::Text, Mode::Current) => render::one_line(md, opts, now),
+        (Format::Text, Mode::Hourly) => render::hourly(md, opts, now),
+        (Format::Text, Mode::Daily) => render::weekly(md, opts, now),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the fixture as a provider returns it when asked for `series` only
+    fn fetched(series: SeriesSet) -> Forecast {
+        let mut md = provider::fixture();
+        if !series.minutely_15 {
+            md.minutely_15 = forecast::Series::default();
+        }
+        if !series.hourly {
+            md.hourly = forecast::Series::default();
+        }
+        if !series.daily_stats {
+            md.daily.temperature_2m_mean.clear();
+            md.daily.relative_humidity_2m_mean.clear();
+        }
+        md
+    }
+
+    // status bars render the current conditions even with --week
+    #[test]
+    fn bars_in_daily_mode() {
+        let clock = FixedClock(1792246020);
+        for format in [Format::Waybar, Format::I3blocks, Format::Polybar] {
+            let md = fetched(Mode::Daily.series(format));
+            let output = render(&md, format, Mode::Daily, &RenderOptions::default(), &clock);
+            assert!(output.contains("11"), "{format:?}: {output}");
+        }
+    }
+
+    // an unknown place is an error rather than the newest cached forecast for anywhere
+    #[test]
+    fn unknown_place() {
+        let place = Err(anyhow!("no ip location"));
+        let error = forecast(place, &FetchOptions::default(), &FixedClock(1792246020));
+        assert_eq!(error.unwrap_err().to_string(), "no ip location");
+    }
+
+    // switching between views keeps each one's forecast cached
+    #[test]
+    fn cache_key_per_series() {
+        let key = |mode: Mode, format: Format| {
+            FetchOptions {
+                series: mode.series(format),
+                ..FetchOptions::default()
+            }
+            .cache_key()
+        };
+        assert_eq!(key(Mode::Current, Format::Text), "mph_mm_hpa_2-14_q");
+        assert_eq!(key(Mode::Hourly, Format::Text), "mph_mm_hpa_2-14_qh");
+        assert_eq!(key(Mode::Daily, Format::Text), "mph_mm_hpa_2-14_hd");
+        assert_eq!(
+            key(Mode::Daily, Format::Json),
+            key(Mode::Daily, Format::Text)
+        );
+        assert_eq!(
+            key(Mode::Daily, Format::Waybar),
+            key(Mode::Current, Format::Text)
+        );
+    }
+
+    #[test]
+    fn views_in_their_modes() {
+        let clock = FixedClock(1792246020);
+        for mode in [Mode::Current, Mode::Hourly, Mode::Daily] {
+            for format in [Format::Text, Format::Json] {
+                let md = fetched(mode.series(format));
+                render(&md, format, mode, &RenderOptions::default(), &clock);
+            }
+        }
+    }
+}
@@ -0,0 +1,211 @@
+// named places like "home" or "cabin" that stand in for their coordinates
+use anyhow::{anyhow, Result};
+use clap::{parser::ValueSource, ArgMatches, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io::ErrorKind, path::PathBuf};
+
+use crate::{config, Settings};
+use weather_rs::units::{PrecipUnit, Preset, PressureUnit, WindUnit};
+use weather_rs::{geo, provider::Place, LatLon, TempScale};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Location {
+    pub lat: f64,
+    pub lon: f64,
+    // IANA name like "America/Chicago", looked up from the coordinates when missing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    // overrides of the config file, the command line still wins
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<TempScale>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub units: Option<Preset>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wind_unit: Option<WindUnit>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub precip_unit: Option<PrecipUnit>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pressure_unit: Option<PressureUnit>,
+}
+
+pub type Locations = BTreeMap<String, Location>;
+
+// $XDG_CONFIG_HOME/weather-rs/locations.toml, next to the config file
+pub fn path() -> Option<PathBuf> {
+    Some(config::dir()?.join("locations.toml"))
+}
+
+// every saved location, none when the file doesn't exist yet
+pub fn load() -> Result<Locations> {
+    let path = path().ok_or_else(|| anyhow!("could not determine config directory"))?;
+    match fs::read_to_string(&path) {
+        Ok(content) => {
+            toml::from_str(&content).map_err(|e| anyhow!("{}: {}", path.display(), e.message()))
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(Locations::new()),
+        Err(e) => Err(anyhow!("failed to read {}: {e}", path.display())),
+    }
+}
+
+fn save(locations: &Locations) -> Result<()> {
+    let path = path().ok_or_else(|| anyhow!("could not determine config directory"))?;
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(path, toml::to_string(locations)?)?;
+    Ok(())
+}
+
+// names are matched ignoring case, so "Home" finds "home"
+pub fn find<'a>(locations: &'a Locations, name: &str) -> Option<(&'a String, &'a Location)> {
+    let name = name.trim();
+    locations
+        .iter()
+        .find(|(saved, _)| saved.eq_ignore_ascii_case(name))
+}
+
+// saves `location` as `name`, returning whether it replaced an existing one
+pub fn add(name: &str, location: Location) -> Result<bool> {
+    let mut locations = load()?;
+    let replaced = match find(&locations, name) {
+        Some((saved, _)) => locations.remove(&saved.clone()).is_some(),
+        None => false,
+    };
+    locations.insert(name.trim().to_string(), location);
+    save(&locations)?;
+    Ok(replaced)
+}
+
+// forgets the location `name` refers to, returning its name as saved
+pub fn remove(name: &str) -> Result<String> {
+    let mut locations = load()?;
+    let Some((saved, _)) = find(&locations, name) else {
+        return Err(anyhow!("no saved location \"{name}\""));
+    };
+    let saved = saved.clone();
+    locations.remove(&saved);
+    save(&locations)?;
+    Ok(saved)
+}
+
+impl Location {
+    pub fn latlon(&self) -> LatLon {
+        LatLon {
+            lat: self.lat,
+            lon: self.lon,
+        }
+    }
+
+    pub fn place(&self) -> Place {
+        match &self.timezone {
+            Some(timezone) => Place {
+                latlon: self.latlon(),
+                timezone: timezone.clone(),
+            },
+            None => geo::place_at(self.latlon()),
+        }
+    }
+
+    // "imperial, °C, kn" for the overrides that are set
+    pub fn overrides(&self) -> String {
+        let preset = self.units.and_then(|p| p.to_possible_value());
+        [
+            preset.map(|p| p.get_name().to_string()),
+            self.temperature.map(|t| t.label().to_string()),
+            self.wind_unit.map(|u| u.label().to_string()),
+            self.precip_unit.map(|u| u.label().to_string()),
+            self.pressure_unit.map(|u| u.label().to_string()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+
+    // the location's units in place of the config file's, unless the flag was given
+    pub fn apply(&self, settings: &mut Settings, matches: &ArgMatches) {
+        let from_cli = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+        // a preset on the command line stands for every unit, the location's included
+        if from_cli("units") {
+            return;
+        }
+        let temperature_from_cli =
+            from_cli("fahrenheit") || from_cli("celsius") || from_cli("kelvin");
+
+        // a preset stands for every unit, so it replaces the config file's individual ones too
+        if self.units.is_some() {
+            settings.units = self.units;
+            if !temperature_from_cli {
+                settings.fahrenheit = false;
+                settings.celsius = false;
+                settings.kelvin = false;
+            }
+            if !from_cli("wind_unit") {
+                settings.wind_unit = None;
+            }
+            if !from_cli("precip_unit") {
+                settings.precip_unit = None;
+            }
+            if !from_cli("pressure_unit") {
+                settings.pressure_unit = None;
+            }
+        }
+        if let Some(scale) = self.temperature.filter(|_| !temperature_from_cli) {
+            settings.fahrenheit = scale == TempScale::Fahrenheit;
+            settings.celsius = scale == TempScale::Celsius;
+            settings.kelvin = scale == TempScale::Kelvin;
+        }
+        if self.wind_unit.is_some() && !from_cli("wind_unit") {
+            settings.wind_unit = self.wind_unit;
+        }
+        if self.precip_unit.is_some() && !from_cli("precip_unit") {
+            settings.precip_unit = self.precip_unit;
+        }
+        if self.pressure_unit.is_some() && !from_cli("pressure_unit") {
+            settings.pressure_unit = self.pressure_unit;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches};
+
+    // settings from `args` with the location `content` applied
+    fn settings(content: &str, args: &[&str]) -> Settings {
+        let matches = Settings::command()
+            .try_get_matches_from(["weather-rs"].iter().chain(args))
+            .unwrap();
+        let mut settings = Settings::from_arg_matches(&matches).unwrap();
+        let location: Location = toml::from_str(content).unwrap();
+        location.apply(&mut settings, &matches);
+        settings
+    }
+
+    const CABIN: &str = "lat = 46.5\nlon = -90.1\ntemperature = \"c\"\nwind-unit = \"kn\"\n";
+
+    #[test]
+    fn overrides() {
+        let s = settings(CABIN, &[]);
+        assert_eq!(s.temp_scale(), TempScale::Celsius);
+        assert_eq!(s.unit_system().wind, WindUnit::Kn);
+        // single flags win over their own field only
+        let s = settings(CABIN, &["-f"]);
+        assert_eq!(s.temp_scale(), TempScale::Fahrenheit);
+        assert_eq!(s.unit_system().wind, WindUnit::Kn);
+    }
+
+    #[test]
+    fn units_from_cli() {
+        // a preset on the command line beats every unit of the location
+        let s = settings(CABIN, &["--units", "imperial"]);
+        assert_eq!(s.temp_scale(), TempScale::Fahrenheit);
+        assert_eq!(s.unit_system().wind, WindUnit::Mph);
+        let s = settings(
+            "lat = 0.0\nlon = 0.0\nunits = \"imperial\"\n",
+            &["--units", "metric"],
+        );
+        assert_eq!(s.temp_scale(), TempScale::Celsius);
+        assert_eq!(s.unit_system().wind, WindUnit::Kmh);
+    }
+}
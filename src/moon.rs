@@ -0,0 +1,374 @@
+// lunar phase from the low precision ephemeris in Meeus' Astronomical Algorithms,
+// chapter 48 for the illuminated fraction and 49 for the times of new and full moon
+use chrono::{DateTime, Local};
+
+// mean length of a lunation in days
+const SYNODIC_MONTH: f64 = 29.530588861;
+// TT - UT in seconds, it has stayed within a couple of seconds of this since 2015
+const DELTA_T: f64 = 69.0;
+
+// 🌕🌖🌗🌘🌑🌒🌓🌔
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoonPhase {
+    Full,
+    WanGib,
+    LastQ,
+    WanCres,
+    New,
+    WaxCres,
+    FirstQ,
+    WaxGib,
+}
+
+impl MoonPhase {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MoonPhase::Full => "full",
+            MoonPhase::WanGib => "waning_gibbous",
+            MoonPhase::LastQ => "last_quarter",
+            MoonPhase::WanCres => "waning_crescent",
+            MoonPhase::New => "new",
+            MoonPhase::WaxCres => "waxing_crescent",
+            MoonPhase::FirstQ => "first_quarter",
+            MoonPhase::WaxGib => "waxing_gibbous",
+        }
+    }
+
+    // the phase centered on `elongation`, 0° at new through 180° at full back to 360°
+    pub fn from_elongation(elongation: f64) -> MoonPhase {
+        match ((elongation + 22.5) / 45.0) as u32 % 8 {
+            0 => MoonPhase::New,
+            1 => MoonPhase::WaxCres,
+            2 => MoonPhase::FirstQ,
+            3 => MoonPhase::WaxGib,
+            4 => MoonPhase::Full,
+            5 => MoonPhase::WanGib,
+            6 => MoonPhase::LastQ,
+            _ => MoonPhase::WanCres,
+        }
+    }
+
+    pub fn emoji(&self) -> &'static str {
+        match self {
+            MoonPhase::Full => "🌕",
+            MoonPhase::WanGib => "🌖",
+            MoonPhase::LastQ => "🌗",
+            MoonPhase::WanCres => "🌘",
+            MoonPhase::New => "🌑",
+            MoonPhase::WaxCres => "🌒",
+            MoonPhase::FirstQ => "🌓",
+            MoonPhase::WaxGib => "🌔",
+        }
+    }
+}
+
+pub struct Moon {
+    pub phase: MoonPhase,
+    // sun-moon angle as seen from the moon, 0° when full and 180° when new
+    pub phase_angle: f64,
+    // illuminated fraction of the disk, 0.0 to 1.0
+    pub illumination: f64,
+    // days since the last new moon
+    pub age: f64,
+    pub next_new: i64,
+    pub next_full: i64,
+}
+
+fn to_julian(unix: f64) -> f64 {
+    unix / 86400.0 + 2440587.5
+}
+
+fn to_unix(jd: f64) -> i64 {
+    ((jd - 2440587.5) * 86400.0).round() as i64
+}
+
+fn sin(degrees: f64) -> f64 {
+    degrees.to_radians().sin()
+}
+
+// unix time of the new moon for an integer `k`, or the full moon for k + 0.5,
+// with k = 0 being the new moon of 2000-01-06
+fn true_phase(k: f64) -> i64 {
+    let t = k / 1236.85;
+    let (t2, t3, t4) = (t * t, t * t * t, t * t * t * t);
+
+    let mut jde =
+        2451550.09766 + SYNODIC_MONTH * k + 0.00015437 * t2 - 0.000000150 * t3 + 0.00000000073 * t4;
+
+    // eccentricity of earth's orbit
+    let e = 1.0 - 0.002516 * t - 0.0000074 * t2;
+    // sun's and moon's mean anomaly, moon's argument of latitude and ascending node
+    let m = 2.5534 + 29.1053567 * k - 0.0000014 * t2 - 0.00000011 * t3;
+    let mp = 201.5643 + 385.81693528 * k + 0.0107582 * t2 + 0.00001238 * t3 - 0.000000058 * t4;
+    let f = 160.7108 + 390.67050284 * k - 0.0016118 * t2 - 0.00000227 * t3 + 0.000000011 * t4;
+    let omega = 124.7746 - 1.56375588 * k + 0.0020672 * t2 + 0.00000215 * t3;
+
+    // the leading coefficients are the only ones that differ between new and full
+    let (c1, c2, c3, c4, c5, c6, c7) = if k.fract().abs() < 0.25 {
+        (
+            -0.40720, 0.17241, 0.01608, 0.01039, 0.00739, -0.00514, 0.00208,
+        )
+    } else {
+        (
+            -0.40614, 0.17302, 0.01614, 0.01043, 0.00734, -0.00515, 0.00209,
+        )
+    };
+    jde += c1 * sin(mp)
+        + c2 * e * sin(m)
+        + c3 * sin(2.0 * mp)
+        + c4 * sin(2.0 * f)
+        + c5 * e * sin(mp - m)
+        + c6 * e * sin(mp + m)
+        + c7 * e * e * sin(2.0 * m)
+        - 0.00111 * sin(mp - 2.0 * f)
+        - 0.00057 * sin(mp + 2.0 * f)
+        + 0.00056 * e * sin(2.0 * mp + m)
+        - 0.00042 * sin(3.0 * mp)
+        + 0.00042 * e * sin(m + 2.0 * f)
+        + 0.00038 * e * sin(m - 2.0 * f)
+        - 0.00024 * e * sin(2.0 * mp - m)
+        - 0.00017 * sin(omega)
+        - 0.00007 * sin(mp + 2.0 * m)
+        + 0.00004 * sin(2.0 * mp - 2.0 * f)
+        + 0.00004 * sin(3.0 * m)
+        + 0.00003 * sin(mp + m - 2.0 * f)
+        + 0.00003 * sin(2.0 * mp + 2.0 * f)
+        - 0.00003 * sin(mp + m + 2.0 * f)
+        + 0.00003 * sin(mp - m + 2.0 * f)
+        - 0.00002 * sin(mp - m - 2.0 * f)
+        - 0.00002 * sin(3.0 * mp + m)
+        + 0.00002 * sin(4.0 * mp);
+
+    // planetary arguments, (coefficient, constant, rate per lunation)
+    const PLANETARY: [(f64, f64, f64); 13] = [
+        (0.000165, 251.88, 0.016321),
+        (0.000164, 251.83, 26.651886),
+        (0.000126, 349.42, 36.412478),
+        (0.000110, 84.66, 18.206239),
+        (0.000062, 141.74, 53.303771),
+        (0.000060, 207.14, 2.453732),
+        (0.000056, 154.84, 7.306860),
+        (0.000047, 34.52, 27.261239),
+        (0.000042, 207.19, 0.121824),
+        (0.000040, 291.34, 1.844379),
+        (0.000037, 161.72, 24.198154),
+        (0.000035, 239.56, 25.513099),
+        (0.000023, 331.55, 3.592518),
+    ];
+    jde += 0.000325 * sin(299.77 + 0.107408 * k - 0.009173 * t2)
+        + PLANETARY
+            .iter()
+            .map(|(c, a, b)| c * sin(a + b * k))
+            .sum::<f64>();
+
+    to_unix(jde - DELTA_T / 86400.0)
+}
+
+// phase angle in degrees at the given unix time
+fn phase_angle(time: i64) -> f64 {
+    let t = (to_julian(time as f64 + DELTA_T) - 2451545.0) / 36525.0;
+    let (t2, t3, t4) = (t * t, t * t * t, t * t * t * t);
+
+    // mean elongation of the moon, sun's and moon's mean anomaly
+    let d = 297.8501921 + 445267.1114034 * t - 0.0018819 * t2 + t3 / 545868.0 - t4 / 113065000.0;
+    let m = 357.5291092 + 35999.0502909 * t - 0.0001536 * t2 + t3 / 24490000.0;
+    let mp = 134.9633964 + 477198.8675055 * t + 0.0087414 * t2 + t3 / 69699.0 - t4 / 14712000.0;
+
+    180.0 - d.rem_euclid(360.0) - 6.289 * sin(mp) + 2.100 * sin(m)
+        - 1.274 * sin(2.0 * d - mp)
+        - 0.658 * sin(2.0 * d)
+        - 0.214 * sin(2.0 * mp)
+        - 0.110 * sin(d)
+}
+
+impl Moon {
+    pub fn at(time: u32) -> Moon {
+        let time = time as i64;
+
+        // lunation of the last new moon, the estimate can be one off either way
+        let mut k = ((to_julian(time as f64) - 2451550.09766) / SYNODIC_MONTH).floor();
+        if true_phase(k) > time {
+            k -= 1.0;
+        }
+        if true_phase(k + 1.0) <= time {
+            k += 1.0;
+        }
+        let last_new = true_phase(k);
+        let full = true_phase(k + 0.5);
+        let next_new = true_phase(k + 1.0);
+        let waxing = time < full;
+        let next_full = if waxing { full } else { true_phase(k + 1.5) };
+
+        let phase_angle = phase_angle(time).rem_euclid(360.0);
+        let illumination = (1.0 + phase_angle.to_radians().cos()) / 2.0;
+
+        // elongation from the sun, 0° at new through 180° at full back to 360°
+        let angle = phase_angle.min(360.0 - phase_angle);
+        let elongation = if waxing { 180.0 - angle } else { 180.0 + angle };
+
+        Moon {
+            phase: MoonPhase::from_elongation(elongation),
+            phase_angle: angle,
+            illumination,
+            age: (time - last_new) as f64 / 86400.0,
+            next_new,
+            next_full,
+        }
+    }
+}
+
+fn local(time: i64) -> String {
+    DateTime::from_timestamp(time, 0)
+        .map(|dt| {
+            dt.with_timezone(&Local)
+                .format("%a %Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+// the moon at `time` for the `moon` subcommand
+pub fn summary(time: u32) -> String {
+    let moon = Moon::at(time);
+    format!(
+        "phase:        {} {}\n\
+         illumination: {:.0}%\n\
+         phase angle:  {:.1}°\n\
+         age:          {:.1} days\n\
+         next full:    {}\n\
+         next new:     {}\n",
+        moon.phase.emoji(),
+        moon.phase.name().replace('_', " "),
+        moon.illumination * 100.0,
+        moon.phase_angle,
+        moon.age,
+        local(moon.next_full),
+        local(moon.next_new),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    // minutes the ephemeris may be off from published times
+    const TOLERANCE: i64 = 3 * 60;
+
+    fn utc(s: &str) -> i64 {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M")
+            .unwrap()
+            .and_utc()
+            .timestamp()
+    }
+
+    fn assert_close(computed: i64, published: &str) {
+        let diff = computed - utc(published);
+        assert!(
+            diff.abs() <= TOLERANCE,
+            "{published}: off by {diff}s ({})",
+            DateTime::from_timestamp(computed, 0).unwrap()
+        );
+    }
+
+    // the phase a minute after `time`, so the new and full moon instants have passed
+    fn after(time: &str) -> Moon {
+        Moon::at((utc(time) + 60) as u32)
+    }
+
+    // USNO phases of the moon, UT
+    #[test]
+    fn new_moons() {
+        for published in [
+            "2000-01-06 18:14",
+            "2017-08-21 18:30",
+            "2024-04-08 18:21",
+            "2025-01-29 12:36",
+        ] {
+            let moon = Moon::at((utc(published) - 86400) as u32);
+            assert_close(moon.next_new, published);
+            let moon = after(published);
+            assert!(moon.age < 0.01, "{published}: age {}", moon.age);
+            assert_eq!(moon.phase, MoonPhase::New);
+        }
+    }
+
+    #[test]
+    fn full_moons() {
+        for published in [
+            "2024-01-25 17:54",
+            "2024-03-25 07:00",
+            "2025-01-13 22:27",
+            "2025-09-07 18:09",
+        ] {
+            let moon = Moon::at((utc(published) - 86400) as u32);
+            assert_close(moon.next_full, published);
+            assert_eq!(after(published).phase, MoonPhase::Full);
+        }
+    }
+
+    // quarters aren't computed, at their published times the disk is half lit
+    #[test]
+    fn quarters() {
+        for (published, phase) in [
+            ("2024-01-04 03:30", MoonPhase::LastQ),
+            ("2024-01-18 03:53", MoonPhase::FirstQ),
+            ("2025-01-06 23:56", MoonPhase::FirstQ),
+            ("2025-01-21 20:31", MoonPhase::LastQ),
+        ] {
+            let moon = Moon::at(utc(published) as u32);
+            assert_eq!(moon.phase, phase, "{published}");
+            assert!(
+                (moon.illumination - 0.5).abs() < 0.01,
+                "{published}: {}",
+                moon.illumination
+            );
+            assert!((moon.phase_angle - 90.0).abs() < 1.0);
+        }
+    }
+
+    #[test]
+    fn illumination() {
+        // Meeus' example 48.a, 1992-04-12 00:00 TD has 0.6786 lit
+        let moon = Moon::at(utc("1992-04-11 23:59") as u32);
+        assert!(
+            (moon.illumination - 0.6786).abs() < 0.005,
+            "{}",
+            moon.illumination
+        );
+        // 111° from the sun, still in the first quarter's 45°
+        assert_eq!(moon.phase, MoonPhase::FirstQ);
+
+        assert!(after("2024-01-25 17:54").illumination > 0.99);
+        assert!(after("2024-04-08 18:21").illumination < 0.01);
+    }
+
+    #[test]
+    fn phase_boundaries() {
+        let cases = [
+            (0.0, MoonPhase::New),
+            (22.4, MoonPhase::New),
+            (22.6, MoonPhase::WaxCres),
+            (67.4, MoonPhase::WaxCres),
+            (67.6, MoonPhase::FirstQ),
+            (112.4, MoonPhase::FirstQ),
+            (112.6, MoonPhase::WaxGib),
+            (157.6, MoonPhase::Full),
+            (202.4, MoonPhase::Full),
+            (202.6, MoonPhase::WanGib),
+            (247.6, MoonPhase::LastQ),
+            (292.4, MoonPhase::LastQ),
+            (292.6, MoonPhase::WanCres),
+            (337.4, MoonPhase::WanCres),
+            (337.6, MoonPhase::New),
+            (360.0, MoonPhase::New),
+        ];
+        for (elongation, phase) in cases {
+            assert_eq!(
+                MoonPhase::from_elongation(elongation),
+                phase,
+                "{elongation}°"
+            );
+        }
+    }
+}
@@ -0,0 +1,106 @@
+// choosing between places that share a name, "Springfield" has a few dozen
+use anyhow::{anyhow, Result};
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use weather_rs::geo::{self, GeocodingResult, SearchOptions};
+use weather_rs::width::{display_width, pad, Align};
+use weather_rs::Vs16;
+
+// "116,250"
+fn thousands(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+// "Springfield, Illinois, United States"
+pub fn describe(r: &GeocodingResult) -> String {
+    [Some(&r.name), r.admin1.as_ref(), r.country.as_ref()]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// lists the candidates on stderr and reads a number from stdin, enter takes the first
+fn prompt(name: &str, candidates: &[GeocodingResult]) -> Result<usize> {
+    let mut err = io::stderr().lock();
+    writeln!(err, "Several places match \"{name}\":")?;
+    let width = candidates
+        .iter()
+        .map(|c| display_width(&describe(c), Vs16::Wide))
+        .max()
+        .unwrap_or(0);
+    for (i, c) in candidates.iter().enumerate() {
+        let population = c
+            .population
+            .map(|p| format!("pop. {}", thousands(p)))
+            .unwrap_or_default();
+        writeln!(
+            err,
+            "{:>3}) {}  {:>15}  ({:.4}, {:.4})",
+            i + 1,
+            pad(&describe(c), width, &Align::Left, Vs16::Wide),
+            population,
+            c.latitude,
+            c.longitude
+        )?;
+    }
+
+    let mut stdin = io::stdin().lock();
+    loop {
+        write!(err, "Pick one [1-{}]: ", candidates.len())?;
+        err.flush()?;
+        let mut line = String::new();
+        if stdin.read_line(&mut line)? == 0 {
+            return Err(anyhow!("no place picked for \"{name}\""));
+        }
+        match line.trim() {
+            "" => return Ok(0),
+            n => match n.parse::<usize>() {
+                Ok(n) if (1..=candidates.len()).contains(&n) => return Ok(n - 1),
+                _ => writeln!(err, "Enter a number between 1 and {}", candidates.len())?,
+            },
+        }
+    }
+}
+
+// the place `name` stands for: an earlier pick, the `pick`th candidate, one chosen on the
+// terminal or the best match when nobody can be asked
+pub fn place(name: &str, opts: &SearchOptions, pick: Option<usize>) -> Result<GeocodingResult> {
+    if pick.is_none() {
+        if let Some(result) = geo::remembered(name, opts) {
+            return Ok(result);
+        }
+    }
+    let mut candidates = geo::search(name, opts)?;
+    let chosen = match pick {
+        Some(n) if n == 0 || n > candidates.len() => {
+            return Err(anyhow!(
+                "--pick {n} is out of range, \"{name}\" has {} matches",
+                candidates.len()
+            ))
+        }
+        Some(n) => n - 1,
+        None if candidates.len() == 1 => 0,
+        None if io::stdin().is_terminal() && io::stderr().is_terminal() => {
+            prompt(name, &candidates)?
+        }
+        None => return Ok(candidates.remove(0)),
+    };
+    let result = candidates.remove(chosen);
+    // a single match is no choice worth remembering
+    if pick.is_some() || !candidates.is_empty() {
+        if let Err(e) = geo::remember(name, opts, &result) {
+            eprintln!("Warning: could not remember the pick: {e}");
+        }
+    }
+    Ok(result)
+}
@@ -0,0 +1,28 @@
+// weather data sources, each one normalizes its response into a Forecast
+use anyhow::Result;
+
+use crate::forecast::Forecast;
+use crate::{FetchOptions, LatLon};
+
+mod met_norway;
+mod open_meteo;
+
+pub use met_norway::MetNorway;
+pub use open_meteo::OpenMeteo;
+
+// where to get a forecast for, timezone is an IANA name like "America/Chicago"
+pub struct Place {
+    pub latlon: LatLon,
+    pub timezone: String,
+}
+
+pub trait WeatherProvider {
+    // identifies the source of cached forecasts
+    fn name(&self) -> &'static str;
+
+    // `now` decides which sample is current for providers that don't report it
+    fn fetch(&self, place: &Place, opts: &FetchOptions, now: u64) -> Result<Forecast>;
+}
+
+#[cfg(test)]
+pub(crate) use open_meteo::fixture;
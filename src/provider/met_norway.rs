@@ -0,0 +1,481 @@
+// api.met.no Locationforecast, hourly for the first couple of days and 6 hourly
+// after that, resampled onto the same grids open-meteo returns
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveTime, Offset, TimeZone};
+use chrono_tz::{OffsetName, Tz};
+
+use super::{Place, WeatherProvider};
+use crate::forecast::{Current, Daily, Forecast, Series, Units};
+use crate::structs::{MetResponse, MetTimestep};
+use crate::units::UnitSystem;
+use crate::wmo::WmoCode;
+use crate::{request_api, FetchOptions, TempScale};
+
+const MET_URL: &str = "https://api.met.no/weatherapi/locationforecast/2.0/complete";
+
+pub struct MetNorway;
+
+// one timestep of the response, temperatures in °C and the rest in display units
+struct Point {
+    time: i64,
+    temperature: f64,
+    humidity: f64,
+    dew_point: f64,
+    apparent_temperature: f64,
+    // per hour over the period after this point
+    precipitation: f64,
+    pressure: f64,
+    wind_speed: f64,
+    wind_direction: f64,
+    uv_index: f64,
+    weather_code: u8,
+    precipitation_probability: f64,
+}
+
+// symbol codes look like "lightrainshowers_day", see api.met.no/weatherapi/weathericon
+fn symbol_to_wmo(symbol: &str) -> u8 {
+    match symbol.split('_').next().unwrap_or(symbol) {
+        s if s.contains("thunder") => 95,
+        "clearsky" => 0,
+        "fair" => 1,
+        "partlycloudy" => 2,
+        "cloudy" => 3,
+        "fog" => 45,
+        "lightrain" => 61,
+        "rain" => 63,
+        "heavyrain" => 65,
+        "lightsnow" | "lightsleet" => 71,
+        "snow" | "sleet" => 73,
+        "heavysnow" | "heavysleet" => 75,
+        "lightrainshowers" => 80,
+        "rainshowers" => 81,
+        "heavyrainshowers" => 82,
+        "lightsnowshowers" | "snowshowers" | "lightsleetshowers" | "sleetshowers" => 85,
+        "heavysnowshowers" | "heavysleetshowers" => 86,
+        _ => u8::MAX,
+    }
+}
+
+// Steadman's apparent temperature without radiation, the formula open-meteo uses,
+// from °C, % and m/s
+fn apparent_temperature(celsius: f64, humidity: f64, wind_speed: f64) -> f64 {
+    let vapour_pressure = humidity / 100.0 * 6.105 * (17.27 * celsius / (237.7 + celsius)).exp();
+    celsius + 0.33 * vapour_pressure - 0.70 * wind_speed - 4.00
+}
+
+fn point(step: &MetTimestep, units: &UnitSystem) -> Result<Point> {
+    let details = &step.data.instant.details;
+    // the shortest period available describes this timestep best
+    let (period, hours) = match (&step.data.next_1_hours, &step.data.next_6_hours) {
+        (Some(p), _) => (Some(p), 1.0),
+        (None, p) => (p.as_ref(), 6.0),
+    };
+    Ok(Point {
+        time: DateTime::parse_from_rfc3339(&step.time)?.timestamp(),
+        temperature: details.air_temperature,
+        humidity: details.relative_humidity,
+        dew_point: details.dew_point_temperature.unwrap_or(f64::NAN),
+        apparent_temperature: apparent_temperature(
+            details.air_temperature,
+            details.relative_humidity,
+            details.wind_speed,
+        ),
+        precipitation: units.precipitation.from_mm(
+            period
+                .and_then(|p| p.details.as_ref()?.precipitation_amount)
+                .unwrap_or(0.0)
+                / hours,
+        ),
+        pressure: units
+            .pressure
+            .from_hpa(details.air_pressure_at_sea_level.unwrap_or(f64::NAN)),
+        wind_speed: units.wind.from_ms(details.wind_speed),
+        wind_direction: details.wind_from_direction,
+        uv_index: details.ultraviolet_index_clear_sky.unwrap_or(0.0),
+        weather_code: period.map_or(u8::MAX, |p| symbol_to_wmo(&p.summary.symbol_code)),
+        precipitation_probability: period
+            .and_then(|p| p.details.as_ref()?.probability_of_precipitation)
+            .unwrap_or(0.0),
+    })
+}
+
+// samples every `step` seconds from the first to the last point, linear in between,
+// codes and probabilities describe the period after a point so they are held instead
+fn resample(points: &[Point], step: i64) -> Series {
+    let mut s = Series::default();
+    let (first, last) = (points[0].time, points[points.len() - 1].time);
+
+    let mut j = 0;
+    for t in (first..=last).step_by(step as usize) {
+        while j + 2 < points.len() && points[j + 1].time <= t {
+            j += 1;
+        }
+        let a = &points[j];
+        let b = &points[(j + 1).min(points.len() - 1)];
+        let f = match b.time - a.time {
+            0 => 0.0,
+            span => (t - a.time) as f64 / span as f64,
+        };
+        // rounded to a tenth like open-meteo's values
+        let lerp = |x: f64, y: f64| ((x + (y - x) * f) * 10.0).round() / 10.0;
+
+        s.time.push(t as u32);
+        s.temperature_2m.push(lerp(a.temperature, b.temperature));
+        s.relative_humidity_2m
+            .push(lerp(a.humidity, b.humidity).round());
+        s.dew_point_2m.push(lerp(a.dew_point, b.dew_point));
+        s.apparent_temperature
+            .push(lerp(a.apparent_temperature, b.apparent_temperature));
+        // hundredths so inches don't round away
+        s.precipitation
+            .push((a.precipitation * step as f64 / 3600.0 * 100.0).round() / 100.0);
+        s.pressure_msl.push(lerp(a.pressure, b.pressure));
+        s.precipitation_probability
+            .push(a.precipitation_probability);
+        s.weather_code.push(a.weather_code);
+        s.wind_speed_10m.push(lerp(a.wind_speed, b.wind_speed));
+        s.wind_direction_10m.push(a.wind_direction.round() as i16);
+    }
+    s
+}
+
+// NOAA sunrise equation, `noon` is any time near local noon of the day
+fn sun_times(lat: f64, lon: f64, noon: i64) -> (u32, u32) {
+    let jd = noon as f64 / 86400.0 + 2440587.5;
+    let n = (jd - 2451545.0 + 0.0008 + lon / 360.0).round();
+    let mean_noon = n - lon / 360.0;
+
+    let m = (357.5291 + 0.98560028 * mean_noon)
+        .rem_euclid(360.0)
+        .to_radians();
+    let c = 1.9148 * m.sin() + 0.02 * (2.0 * m).sin() + 0.0003 * (3.0 * m).sin();
+    let lambda = (m.to_degrees() + c + 180.0 + 102.9372)
+        .rem_euclid(360.0)
+        .to_radians();
+    let transit = 2451545.0 + mean_noon + 0.0053 * m.sin() - 0.0069 * (2.0 * lambda).sin();
+
+    let sin_decl = lambda.sin() * 23.4397f64.to_radians().sin();
+    let cos_decl = sin_decl.asin().cos();
+    let phi = lat.to_radians();
+    let cos_hour = ((-0.833f64).to_radians().sin() - phi.sin() * sin_decl) / (phi.cos() * cos_decl);
+
+    let to_unix = |j: f64| ((j - 2440587.5) * 86400.0) as u32;
+    match cos_hour {
+        // polar day, the sun never sets
+        x if x < -1.0 => (to_unix(transit - 0.5), to_unix(transit + 0.5)),
+        // polar night, a zero length day
+        x if x > 1.0 => (to_unix(transit), to_unix(transit)),
+        x => {
+            let half_day = x.acos().to_degrees() / 360.0;
+            (to_unix(transit - half_day), to_unix(transit + half_day))
+        }
+    }
+}
+
+// aggregates the hourly series into local days
+fn daily(points: &[Point], hourly: &Series, tz: Tz, place: &Place) -> Daily {
+    let mut d = Daily::default();
+    let to_local = |t: i64| tz.timestamp_opt(t, 0).unwrap().date_naive();
+
+    let mut date = to_local(points[0].time);
+    while date <= to_local(points[points.len() - 1].time) {
+        let midnight = |date: chrono::NaiveDate| {
+            tz.from_local_datetime(&date.and_time(NaiveTime::MIN))
+                .earliest()
+                .map_or(0, |dt| dt.timestamp())
+        };
+        let start = midnight(date);
+        let end = date.succ_opt().map_or(i64::MAX, midnight);
+        date = date.succ_opt().unwrap_or(chrono::NaiveDate::MAX);
+
+        let range = hourly.time.partition_point(|t| (*t as i64) < start)
+            ..hourly.time.partition_point(|t| (*t as i64) < end);
+        if range.is_empty() {
+            continue;
+        }
+        let max = |v: &[f64]| v.iter().copied().reduce(f64::max).unwrap_or(f64::NAN);
+        let min = |v: &[f64]| v.iter().copied().reduce(f64::min).unwrap_or(f64::NAN);
+        let mean = |v: &[f64]| v.iter().sum::<f64>() / v.len() as f64;
+        let uv: Vec<f64> = points
+            .iter()
+            .filter(|p| (start..end).contains(&p.time))
+            .map(|p| p.uv_index)
+            .collect();
+        let (sunrise, sunset) = sun_times(place.latlon.lat, place.latlon.lon, start + 12 * 3600);
+
+        d.time.push(start as u32);
+        d.temperature_2m_max
+            .push(max(&hourly.temperature_2m[range.clone()]));
+        d.temperature_2m_min
+            .push(min(&hourly.temperature_2m[range.clone()]));
+        d.temperature_2m_mean
+            .push(mean(&hourly.temperature_2m[range.clone()]));
+        let humidity = &hourly.relative_humidity_2m[range.clone()];
+        d.relative_humidity_2m_max.push(max(humidity));
+        d.relative_humidity_2m_min.push(min(humidity));
+        d.relative_humidity_2m_mean.push(mean(humidity));
+        d.wind_speed_10m_min
+            .push(min(&hourly.wind_speed_10m[range.clone()]));
+        d.wind_speed_10m_mean
+            .push(mean(&hourly.wind_speed_10m[range.clone()]));
+        d.sunrise.push(sunrise);
+        d.sunset.push(sunset);
+        d.precipitation_probability_max
+            .push(max(&hourly.precipitation_probability[range.clone()]) as i32);
+        d.wind_speed_10m_max
+            .push(max(&hourly.wind_speed_10m[range.clone()]));
+        // the day's worst weather
+        d.weather_code.push(
+            hourly.weather_code[range]
+                .iter()
+                .copied()
+                .max_by_key(|c| (WmoCode::from_code(*c).map(WmoCode::severity), *c))
+                .unwrap_or(u8::MAX),
+        );
+        d.uv_index_max
+            .push(if uv.is_empty() { 0.0 } else { max(&uv) });
+    }
+    d
+}
+
+impl WeatherProvider for MetNorway {
+    fn name(&self) -> &'static str {
+        "met-norway"
+    }
+
+    fn fetch(&self, place: &Place, opts: &FetchOptions, now: u64) -> Result<Forecast> {
+        // api.met.no asks for at most 4 decimals to keep its caches effective
+        let latlon = place.latlon.snap(opts.grid);
+        let url = format!("{MET_URL}?lat={:.4}&lon={:.4}", latlon.lat, latlon.lon);
+        forecast(request_api(&url)?, place, opts, now)
+    }
+}
+
+// normalizes a response, kept apart from fetch so recorded responses can be replayed.
+// there is no history and about nine days of forecast, longer windows get what there is
+fn forecast(
+    response: MetResponse,
+    place: &Place,
+    opts: &FetchOptions,
+    now: u64,
+) -> Result<Forecast> {
+    let units = &opts.units;
+    let end = now as i64 + opts.window.days as i64 * 86400;
+    let points = response
+        .properties
+        .timeseries
+        .iter()
+        .map(|step| point(step, units))
+        .filter(|p| !p.as_ref().is_ok_and(|p| p.time >= end))
+        .collect::<Result<Vec<Point>>>()?;
+    if points.is_empty() {
+        return Err(anyhow!("MET Norway returned an empty timeseries"));
+    }
+
+    let tz: Tz = place
+        .timezone
+        .parse()
+        .map_err(|e| anyhow!("Unknown timezone {:?}: {e}", place.timezone))?;
+    let now = now as i64;
+    let offset = tz.offset_from_utc_datetime(
+        &DateTime::from_timestamp(now, 0)
+            .unwrap_or_default()
+            .naive_utc(),
+    );
+
+    let hourly = resample(&points, 3600);
+    let minutely_15 = resample(&points, 900);
+    let daily = daily(&points, &hourly, tz, place);
+
+    let current_time = now - now % 900;
+    let i = minutely_15
+        .time
+        .iter()
+        .rposition(|t| *t as i64 <= current_time)
+        .unwrap_or(0);
+    let current = Current {
+        time: current_time as u32,
+        temperature_2m: minutely_15.temperature_2m[i],
+        relative_humidity_2m: minutely_15.relative_humidity_2m[i],
+        weather_code: minutely_15.weather_code[i],
+    };
+
+    let units = Units {
+        temperature: TempScale::Celsius.label().to_string(),
+        relative_humidity: "%".to_string(),
+        precipitation_probability: "%".to_string(),
+        wind_speed: units.wind.label().to_string(),
+        wind_direction: "°".to_string(),
+        precipitation: units.precipitation.label().to_string(),
+        pressure: units.pressure.label().to_string(),
+    };
+
+    let coordinates = &response.geometry.coordinates;
+    Ok(Forecast {
+        provider: MetNorway.name().to_string(),
+        latitude: coordinates.get(1).copied().unwrap_or(place.latlon.lat),
+        longitude: coordinates.first().copied().unwrap_or(place.latlon.lon),
+        elevation: coordinates.get(2).copied().unwrap_or(0.0),
+        timezone: place.timezone.clone(),
+        timezone_abbreviation: offset.abbreviation().unwrap_or_default().to_string(),
+        utc_offset_seconds: offset.fix().local_minus_utc() as i64,
+        units,
+        current,
+        hourly,
+        minutely_15,
+        daily,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::Preset;
+    use crate::LatLon;
+    use chrono::Timelike;
+
+    // 2026-10-17 00:00 in Chicago, the response starts at 07:00 and ends two days later
+    const DAY0: u32 = 1792213200;
+    // 2026-10-17 14:07 UTC
+    const NOW: u64 = 1792246020;
+
+    fn replay(timezone: &str, units: UnitSystem, now: u64) -> Result<Forecast> {
+        let response = serde_json::from_str(include_str!("../../tests/fixtures/met_norway.json"));
+        let place = Place {
+            latlon: LatLon {
+                lat: 41.88,
+                lon: -87.63,
+            },
+            timezone: timezone.to_string(),
+        };
+        let opts = FetchOptions {
+            provider: crate::Provider::MetNorway,
+            units,
+            ..FetchOptions::default()
+        };
+        forecast(response.unwrap(), &place, &opts, now)
+    }
+
+    fn chicago() -> Forecast {
+        replay("America/Chicago", UnitSystem::default(), NOW).unwrap()
+    }
+
+    fn assert_lengths(s: &Series, len: usize) {
+        assert_eq!(s.time.len(), len);
+        for values in [
+            &s.temperature_2m,
+            &s.relative_humidity_2m,
+            &s.dew_point_2m,
+            &s.apparent_temperature,
+            &s.precipitation,
+            &s.pressure_msl,
+            &s.precipitation_probability,
+            &s.wind_speed_10m,
+        ] {
+            assert_eq!(values.len(), len);
+        }
+        assert_eq!(s.weather_code.len(), len);
+        assert_eq!(s.wind_direction_10m.len(), len);
+    }
+
+    #[test]
+    fn series_lengths() {
+        // 48 hours from the first to the last timestep, both ends included
+        let md = chicago();
+        assert_lengths(&md.hourly, 49);
+        assert_lengths(&md.minutely_15, 193);
+        assert!(md.hourly.time.windows(2).all(|w| w[1] - w[0] == 3600));
+        assert!(md.minutely_15.time.windows(2).all(|w| w[1] - w[0] == 900));
+        // three local days are touched, each with every daily value
+        assert_eq!(md.daily.time, [DAY0, DAY0 + 86400, DAY0 + 2 * 86400]);
+        assert_eq!(md.daily.temperature_2m_mean.len(), 3);
+        assert_eq!(md.daily.sunrise.len(), 3);
+        assert_eq!(md.daily.uv_index_max.len(), 3);
+    }
+
+    #[test]
+    fn resampling() {
+        let md = chicago();
+        assert_eq!(md.hourly.temperature_2m[0], 8.0);
+        assert_eq!(md.hourly.temperature_2m[2], 11.0);
+        // a quarter of the way from 11.0 to 12.6
+        assert_eq!(md.minutely_15.temperature_2m[9], 11.4);
+        // 2.4mm over the 6 hours after the first 6 hourly step
+        assert_eq!(md.hourly.precipitation[24], 0.4);
+        assert_eq!(md.hourly.precipitation[27], 0.4);
+        assert_eq!(md.minutely_15.precipitation[24 * 4], 0.1);
+        // codes and probabilities are held over their period
+        assert_eq!(md.hourly.weather_code[0], 0);
+        assert_eq!(md.hourly.weather_code[24..30], [61; 6]);
+        assert_eq!(md.hourly.precipitation_probability[27], 66.0);
+    }
+
+    #[test]
+    fn units() {
+        let md = chicago();
+        assert_eq!(md.provider, "met-norway");
+        assert_eq!(md.units.temperature, "°C");
+        assert_eq!(md.units.wind_speed, "mp/h");
+        assert_eq!(md.units.precipitation, "mm");
+        assert_eq!(md.units.pressure, "hPa");
+        // 4.4 m/s
+        assert_eq!(md.hourly.wind_speed_10m[0], 9.8);
+        assert_eq!(md.hourly.pressure_msl[0], 1015.2);
+
+        let md = replay("America/Chicago", Preset::Metric.into(), NOW).unwrap();
+        assert_eq!(md.units.wind_speed, "km/h");
+        assert_eq!(md.hourly.wind_speed_10m[0], 15.8);
+
+        let md = replay("America/Chicago", Preset::Imperial.into(), NOW).unwrap();
+        assert_eq!(md.units.precipitation, "inch");
+        assert_eq!(md.units.pressure, "inHg");
+        assert_eq!(md.hourly.precipitation[24], 0.02);
+        assert_eq!(md.hourly.pressure_msl[0], 30.0);
+        // temperatures stay in °C whatever is shown
+        assert_eq!(md.hourly.temperature_2m[0], 8.0);
+    }
+
+    #[test]
+    fn timezone() {
+        let md = chicago();
+        assert_eq!(md.timezone, "America/Chicago");
+        assert_eq!(md.utc_offset_seconds, -18000);
+        assert_eq!(md.timezone_abbreviation, "CDT");
+        assert!(md.daily.time.iter().all(|t| md.local_time(*t).hour() == 0));
+        let sunrise = md.local_time(md.daily.sunrise[0]);
+        assert_eq!((sunrise.hour(), sunrise.minute() / 10), (7, 0));
+
+        // the offset is the one at `now`, days split at the location's midnights
+        let md = replay("Europe/Oslo", UnitSystem::default(), NOW).unwrap();
+        assert_eq!(md.utc_offset_seconds, 7200);
+        assert_eq!(md.timezone_abbreviation, "CEST");
+        assert!(md.daily.time.iter().all(|t| md.local_time(*t).hour() == 0));
+
+        let err = replay("Mars/Olympus", UnitSystem::default(), NOW).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Unknown timezone \"Mars/Olympus\""));
+    }
+
+    #[test]
+    fn current() {
+        let md = chicago();
+        assert_eq!(md.current.time, 1792245600);
+        assert_eq!(md.current.temperature_2m, 11.0);
+        assert_eq!(md.current.weather_code, 0);
+    }
+
+    #[test]
+    fn missing_fields() {
+        let md = chicago();
+        // the last timestep has no period, the one before it describes the time up to it
+        assert!(!md.hourly.weather_code.contains(&u8::MAX));
+        assert_eq!(md.hourly.weather_code[48], md.hourly.weather_code[47]);
+        // the uv index ends with the hourly timesteps
+        assert_eq!(md.daily.uv_index_max[2], 0.0);
+        assert!(md.daily.uv_index_max[0] > 3.0);
+        // a window ending before the first timestep leaves nothing
+        let err = replay("America/Chicago", UnitSystem::default(), NOW - 16 * 86400).unwrap_err();
+        assert_eq!(err.to_string(), "MET Norway returned an empty timeseries");
+    }
+}
@@ -0,0 +1,279 @@
+// open-meteo.com, the default provider
+use anyhow::Result;
+
+use super::{Place, WeatherProvider};
+use crate::forecast::{Current, Daily, Forecast, Series, Units};
+use crate::structs::MeteoApiResponse;
+use crate::units::UnitSystem;
+use crate::{request_api, FetchOptions, TempScale};
+
+pub struct OpenMeteo;
+
+// hourly and minutely_15 have the same fields but distinct types
+macro_rules! series {
+    ($s:expr, $pressure_msl:expr) => {
+        Series {
+            time: $s.time,
+            temperature_2m: $s.temperature_2m,
+            relative_humidity_2m: $s.relative_humidity_2m,
+            dew_point_2m: $s.dew_point_2m,
+            apparent_temperature: $s.apparent_temperature,
+            precipitation: $s.precipitation,
+            pressure_msl: $pressure_msl,
+            precipitation_probability: $s.precipitation_probability,
+            weather_code: $s.weather_code,
+            wind_speed_10m: $s.wind_speed_10m,
+            wind_direction_10m: $s.wind_direction_10m,
+        }
+    };
+}
+
+const HOURLY: &str = "hourly=temperature_2m,relative_humidity_2m,dew_point_2m,apparent_temperature,precipitation,pressure_msl,precipitation_probability,weather_code,wind_speed_10m,wind_direction_10m&";
+const MINUTELY_15: &str = "minutely_15=temperature_2m,relative_humidity_2m,dew_point_2m,apparent_temperature,precipitation,precipitation_probability,weather_code,wind_speed_10m,wind_direction_10m&";
+const DAILY_STATS: &str = ",temperature_2m_mean,relative_humidity_2m_max,relative_humidity_2m_min,relative_humidity_2m_mean,wind_speed_10m_min,wind_speed_10m_mean";
+
+// make a url to request for OpenMeteo
+fn make_meteo_url(place: &Place, opts: &FetchOptions) -> String {
+    let units = &opts.units;
+    let series = &opts.series;
+    let latlon = place.latlon.snap(opts.grid);
+
+    let text = format!(
+        concat!(
+            "http://api.open-meteo.com/v1/forecast?",
+            "latitude={}&", // <--
+            "longitude={}&", // <--
+            "current=temperature_2m,relative_humidity_2m,weather_code&",
+            "{}{}", // <--
+            "daily=temperature_2m_max,temperature_2m_min,sunrise,sunset,precipitation_probability_max,wind_speed_10m_max,weather_code,uv_index_max,uv_index_clear_sky_max{}&",
+            "wind_speed_unit={}&", // <--
+            "precipitation_unit={}&", // <--
+            "timeformat=unixtime&",
+            "timezone={}&", // <--
+            "past_days={}&", // <--
+            "forecast_days={}" // <--
+        ),
+        latlon.lat,
+        latlon.lon,
+        if series.hourly { HOURLY } else { "" },
+        if series.minutely_15 { MINUTELY_15 } else { "" },
+        if series.daily_stats { DAILY_STATS } else { "" },
+        units.wind.key(),
+        units.precipitation.key(),
+        place.timezone,
+        opts.window.past_days,
+        opts.window.days
+    );
+
+    text
+}
+
+// pressure is only offered hourly, each 15 minute sample takes the hour's value
+fn hold_hourly(hourly_time: &[u32], values: &[f64], time: &[u32]) -> Vec<f64> {
+    time.iter()
+        .map(|t| {
+            let i = hourly_time.iter().rposition(|h| h <= t).unwrap_or(0);
+            values.get(i).copied().unwrap_or(f64::NAN)
+        })
+        .collect()
+}
+
+// open-meteo converts everything but pressure itself, labels come from `units` so they
+// match the other providers exactly
+fn forecast(md: MeteoApiResponse, units: &UnitSystem) -> Forecast {
+    let labels = Units {
+        temperature: TempScale::Celsius.label().to_string(),
+        relative_humidity: "%".to_string(),
+        precipitation_probability: "%".to_string(),
+        wind_speed: units.wind.label().to_string(),
+        wind_direction: "°".to_string(),
+        precipitation: units.precipitation.label().to_string(),
+        pressure: units.pressure.label().to_string(),
+    };
+    let pressure: Vec<f64> = md
+        .hourly
+        .pressure_msl
+        .iter()
+        .map(|p| units.pressure.from_hpa(*p))
+        .collect();
+    let minutely_pressure = hold_hourly(&md.hourly.time, &pressure, &md.minutely_15.time);
+    let current = Current {
+        time: md.current.time,
+        temperature_2m: md.current.temperature_2m,
+        relative_humidity_2m: md.current.relative_humidity_2m as f64,
+        weather_code: md.current.weather_code,
+    };
+    let daily = Daily {
+        time: md.daily.time,
+        temperature_2m_max: md.daily.temperature_2m_max,
+        temperature_2m_min: md.daily.temperature_2m_min,
+        temperature_2m_mean: md.daily.temperature_2m_mean,
+        relative_humidity_2m_max: md.daily.relative_humidity_2m_max,
+        relative_humidity_2m_min: md.daily.relative_humidity_2m_min,
+        relative_humidity_2m_mean: md.daily.relative_humidity_2m_mean,
+        wind_speed_10m_min: md.daily.wind_speed_10m_min,
+        wind_speed_10m_mean: md.daily.wind_speed_10m_mean,
+        sunrise: md.daily.sunrise,
+        sunset: md.daily.sunset,
+        precipitation_probability_max: md.daily.precipitation_probability_max,
+        wind_speed_10m_max: md.daily.wind_speed_10m_max,
+        weather_code: md.daily.weather_code,
+        uv_index_max: md.daily.uv_index_max,
+    };
+
+    Forecast {
+        provider: OpenMeteo.name().to_string(),
+        latitude: md.latitude,
+        longitude: md.longitude,
+        elevation: md.elevation,
+        timezone: md.timezone,
+        timezone_abbreviation: md.timezone_abbreviation,
+        utc_offset_seconds: md.utc_offset_seconds,
+        units: labels,
+        current,
+        hourly: series!(md.hourly, pressure),
+        minutely_15: series!(md.minutely_15, minutely_pressure),
+        daily,
+    }
+}
+
+impl WeatherProvider for OpenMeteo {
+    fn name(&self) -> &'static str {
+        "open-meteo"
+    }
+
+    fn fetch(&self, place: &Place, opts: &FetchOptions, _now: u64) -> Result<Forecast> {
+        let md: MeteoApiResponse = request_api(&make_meteo_url(place, opts))?;
+        Ok(forecast(md, &opts.units))
+    }
+}
+
+// the fixture response with every series, for tests that need a whole forecast
+#[cfg(test)]
+pub(crate) fn fixture() -> Forecast {
+    let md = serde_json::from_str(include_str!("../../tests/fixtures/open_meteo.json")).unwrap();
+    forecast(md, &UnitSystem::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::Preset;
+    use chrono::Timelike;
+
+    // 2026-10-17 00:00 in Chicago, the first day of the fixtures
+    const DAY0: u32 = 1792213200;
+
+    fn replay(json: &str, units: &UnitSystem) -> Forecast {
+        forecast(serde_json::from_str(json).unwrap(), units)
+    }
+
+    fn assert_lengths(s: &Series, len: usize) {
+        assert_eq!(s.time.len(), len);
+        for values in [
+            &s.temperature_2m,
+            &s.relative_humidity_2m,
+            &s.dew_point_2m,
+            &s.apparent_temperature,
+            &s.precipitation,
+            &s.pressure_msl,
+            &s.precipitation_probability,
+            &s.wind_speed_10m,
+        ] {
+            assert_eq!(values.len(), len);
+        }
+        assert_eq!(s.weather_code.len(), len);
+        assert_eq!(s.wind_direction_10m.len(), len);
+    }
+
+    #[test]
+    fn series_lengths() {
+        let md = fixture();
+        assert_lengths(&md.minutely_15, 192);
+        assert_lengths(&md.hourly, 48);
+        assert_eq!(md.daily.time, [DAY0, DAY0 + 86400]);
+        assert_eq!(md.daily.temperature_2m_mean.len(), 2);
+        assert_eq!(md.daily.uv_index_max.len(), 2);
+        // evenly spaced
+        assert!(md.minutely_15.time.windows(2).all(|w| w[1] - w[0] == 900));
+        assert!(md.hourly.time.windows(2).all(|w| w[1] - w[0] == 3600));
+    }
+
+    #[test]
+    fn units() {
+        let md = fixture();
+        assert_eq!(md.provider, "open-meteo");
+        assert_eq!(md.units.temperature, "°C");
+        assert_eq!(md.units.wind_speed, "mp/h");
+        assert_eq!(md.units.precipitation, "mm");
+        assert_eq!(md.units.pressure, "hPa");
+        assert_eq!(md.hourly.pressure_msl[0], 1016.2);
+
+        // open-meteo converts wind and precipitation itself, pressure is converted here
+        let json = include_str!("../../tests/fixtures/open_meteo.json");
+        let md = replay(json, &Preset::Imperial.into());
+        assert_eq!(md.units.wind_speed, "mp/h");
+        assert_eq!(md.units.precipitation, "inch");
+        assert_eq!(md.units.pressure, "inHg");
+        assert!((md.hourly.pressure_msl[0] - 30.008).abs() < 0.001);
+        // every 15 minute sample takes its hour's pressure
+        assert_eq!(
+            md.minutely_15.pressure_msl[4..8],
+            [md.hourly.pressure_msl[1]; 4]
+        );
+    }
+
+    #[test]
+    fn timezone() {
+        let md = fixture();
+        assert_eq!(md.timezone, "America/Chicago");
+        assert_eq!(md.utc_offset_seconds, -18000);
+        assert_eq!(md.local_time(DAY0).hour(), 0);
+        assert_eq!(md.local_time(md.current.time).hour(), 9);
+        assert_eq!(md.day_index(md.current.time), 0);
+        assert_eq!(md.day_index(DAY0 + 86400 + 60), 1);
+    }
+
+    #[test]
+    fn current() {
+        let md = fixture();
+        assert_eq!(md.current.time, 1792245600);
+        assert_eq!(md.current.temperature_2m, 11.0);
+        assert_eq!(md.current.relative_humidity_2m, 68.0);
+        assert_eq!(md.current.weather_code, 0);
+    }
+
+    #[test]
+    fn missing_series() {
+        // what the one line view requests: no hourly series and no daily aggregates
+        let json = include_str!("../../tests/fixtures/open_meteo_current.json");
+        let md = replay(json, &UnitSystem::default());
+        assert_lengths(&md.minutely_15, 192);
+        assert!(md.hourly.time.is_empty());
+        assert!(md.hourly.pressure_msl.is_empty());
+        assert!(md.daily.temperature_2m_mean.is_empty());
+        assert!(md.daily.wind_speed_10m_mean.is_empty());
+        assert_eq!(md.daily.temperature_2m_max.len(), 2);
+        // without hourly samples there is no pressure to hold
+        assert!(md.minutely_15.pressure_msl.iter().all(|p| p.is_nan()));
+        assert_eq!(md.pressure_at(md.current.time), None);
+    }
+
+    #[test]
+    fn nulls() {
+        // the 15 minute models end two hours before the response does
+        let json = include_str!("../../tests/fixtures/open_meteo_current.json");
+        let md = replay(json, &UnitSystem::default());
+        let m = &md.minutely_15;
+        assert!(m.temperature_2m[..184].iter().all(|t| !t.is_nan()));
+        assert!(m.temperature_2m[184..].iter().all(|t| t.is_nan()));
+        assert!(m.precipitation_probability[184..]
+            .iter()
+            .all(|p| p.is_nan()));
+        assert_eq!(m.weather_code[184..], [u8::MAX; 8]);
+        assert_eq!(m.wind_direction_10m[184..], [0; 8]);
+        assert_eq!(m.time[191], DAY0 + 2 * 86400 - 900);
+        // the last day has no uv forecast yet
+        assert!(md.daily.uv_index_max[1].is_nan());
+    }
+}
@@ -0,0 +1,704 @@
+// plain text views of a forecast: the one line summary, the hourly and the weekly table
+use chrono::Datelike;
+use std::fmt::Write;
+
+use crate::color::{
+    get_temp_rgb, get_wb_rgb, lerp, reset, rgb_lerp, Rgb, CLEAR_BLUE, DEEP_BLUE, PURPLE, WHITE,
+};
+use crate::column::{layout, Align, Cell, Spec, View};
+use crate::forecast::{Forecast, Stats};
+use crate::moon::{Moon, MoonPhase};
+use crate::template::Style;
+use crate::units::TempDisplay;
+use crate::width::pad;
+use crate::wmo::WmoCode;
+use crate::{compute_wet_bulb, EmojiMode, RenderOptions, Vs16, CACHE_TIMEOUT};
+
+// rows of the hourly table are this many 15 minute samples apart
+fn hourly_res(opts: &RenderOptions) -> usize {
+    let full_res_h = (opts.window.hours_back + opts.window.hours_ahead) as usize;
+    match opts.term_size.1 {
+        x if x <= full_res_h && x > (full_res_h * 2 / 3) => 6,
+        x if x <= (full_res_h * 2 / 3) && x > (full_res_h / 3) => 8,
+        x if x <= (full_res_h / 3) => 12,
+        _ => 4,
+    }
+}
+
+// turn WMO codes into a message
+pub fn wmo_decode(
+    wmo: u8,
+    daynight: bool,
+    moon: &Moon,
+    emoji: EmojiMode,
+) -> (String, &'static Rgb) {
+    let Some(code) = WmoCode::from_code(wmo) else {
+        return (format!("N/A {wmo}"), &CLEAR_BLUE);
+    };
+    let (wmo_s, color) = code.label(&emoji, daynight);
+    let wmo_string_with_moon = wmo_s
+        .replace("%m", moon.phase.emoji())
+        .replace("%i", &format!("{:.0}%", moon.illumination * 100.0));
+    (wmo_string_with_moon, color)
+}
+
+// the values behind the single line weather update
+pub struct Conditions {
+    // °C like wet_bulb, `temperature` says how to show them
+    pub temp: f64,
+    pub humid: f64,
+    pub wind_format: String,
+    pub wind_speed: f64,
+    pub wind_direction: &'static str,
+    // "mp/h", as the forecast was fetched
+    pub wind_unit: String,
+    pub wet_bulb: f64,
+    pub wmo: u8,
+    pub wmo_string: String,
+    pub wmo_rgb: &'static Rgb,
+    pub precip_max: i32,
+    // local "HH:MM"
+    pub sunrise: String,
+    pub sunset: String,
+    pub moon: MoonPhase,
+    pub temperature: TempDisplay,
+    pub stale: Option<u64>,
+}
+
+impl Conditions {
+    pub fn line(&self, vs16: Vs16) -> String {
+        let line = format!(
+            "{} {}% {} {} ~{}%",
+            self.temperature.format(self.temp),
+            self.humid,
+            self.wind_format,
+            pad(&self.wmo_string, 8, &Align::Left, vs16),
+            self.precip_max,
+        );
+        match self.stale {
+            Some(minutes) => format!("{line} ({minutes}m old)"),
+            None => line,
+        }
+    }
+}
+
+pub fn current_conditions(md: &Forecast, opts: &RenderOptions, now: u64) -> Conditions {
+    let time = &md.minutely_15.time;
+    let now_index = get_time_index(time, now);
+
+    let today = md.day_index(time[now_index]);
+
+    let temp = &md.minutely_15.temperature_2m;
+    let humid = &md.minutely_15.relative_humidity_2m;
+    let precip_max = md.daily.precipitation_probability_max[today];
+    let wind_speed = md.minutely_15.wind_speed_10m[now_index];
+    let wind_direction = wind_di_decode(md.minutely_15.wind_direction_10m[now_index]);
+    let wind_format = format!("{1}-{0}", wind_direction, wind_speed);
+    let wmo = &md.minutely_15.weather_code;
+
+    let sunset = md.daily.sunset[today];
+    let sunrise = md.daily.sunrise[today];
+
+    let moon = Moon::at(time[now_index]);
+    let (wmo_string, wmo_rgb) = wmo_decode(
+        wmo[now_index],
+        time[now_index] < sunset && time[now_index] > sunrise,
+        &moon,
+        opts.emoji,
+    );
+    let hh_mm = |t: u32| md.local_time(t).format("%H:%M").to_string();
+
+    Conditions {
+        temp: temp[now_index],
+        humid: humid[now_index],
+        wind_format,
+        wind_speed,
+        wind_direction,
+        wind_unit: md.units.wind_speed.clone(),
+        wet_bulb: compute_wet_bulb(temp[now_index], humid[now_index]),
+        wmo: wmo[now_index],
+        wmo_string,
+        wmo_rgb,
+        precip_max,
+        sunrise: hh_mm(sunrise),
+        sunset: hh_mm(sunset),
+        moon: moon.phase,
+        temperature: opts.temperature,
+        stale: stale_minutes(md, now),
+    }
+}
+
+// a single line weather update, good for status bars
+pub fn one_line(md: &Forecast, opts: &RenderOptions, now: u64) -> String {
+    let c = current_conditions(md, opts, now);
+    let line = match &opts.template {
+        Some(template) if opts.color => template.render(&c, Style::Ansi),
+        Some(template) => template.render(&c, Style::Plain),
+        None => c.line(opts.vs16),
+    };
+    format!("{line}\n")
+}
+
+// makes a bar as val moves between low and high
+pub fn mk_bar(val: &f64, low: &f64, high: &f64, bar_low: &f64, bar_max: usize) -> String {
+    let x = lerp(*val, *low, *high, *bar_low, bar_max as f64 - 1.0);
+    let mut blocks: String = "█".repeat(x as usize);
+    let y = x - x.trunc();
+    let conversion = match y {
+        x if (0.0..1.0 / 8.0).contains(&x) => " ",
+        x if (1.0 / 8.0..2.0 / 8.0).contains(&x) => "▏",
+        x if (2.0 / 8.0..3.0 / 8.0).contains(&x) => "▎",
+        x if (3.0 / 8.0..4.0 / 8.0).contains(&x) => "▍",
+        x if (4.0 / 8.0..5.0 / 8.0).contains(&x) => "▌",
+        x if (5.0 / 8.0..6.0 / 8.0).contains(&x) => "▋",
+        x if (6.0 / 8.0..7.0 / 8.0).contains(&x) => "▊",
+        x if (7.0 / 8.0..1.0).contains(&x) => "▉",
+        _ => "*",
+    };
+    blocks.push_str(conversion);
+    let result = format!("{:1$}", blocks, bar_max);
+    result.to_string()
+}
+
+// turns a 24hr time into am/pm
+pub fn to_am_pm(time: i64) -> String {
+    match time {
+        0 => {
+            format!("{}am", time + 12)
+        }
+        x if x > 0 && x <= 11 => {
+            format!("{time}am")
+        }
+        12 => {
+            format!("{time}pm")
+        }
+        x if (13..=23).contains(&x) => {
+            format!("{}pm", time - 12)
+        }
+        _ => {
+            format!("{time}*")
+        }
+    }
+}
+
+// the sample `now` falls in, clamped to the first and last. outside the data the forecast is
+// old, which stale_minutes tells
+pub fn get_time_index(time_data: &[u32], now: u64) -> usize {
+    time_data
+        .partition_point(|t| *t as u64 <= now)
+        .saturating_sub(1)
+}
+
+pub fn wind_di_decode(di: i16) -> &'static str {
+    match di as f64 {
+        x if (337.5..=360.0).contains(&x) => "N",
+        x if (0.0..=22.5).contains(&x) => "N",
+        x if (22.5..=67.5).contains(&x) => "NE",
+        x if (67.5..=112.5).contains(&x) => "E",
+        x if (112.5..=157.5).contains(&x) => "SE",
+        x if (157.5..=202.5).contains(&x) => "S",
+        x if (202.5..=247.5).contains(&x) => "SW",
+        x if (247.5..=292.5).contains(&x) => "W",
+        x if (292.5..=337.5).contains(&x) => "NW",
+        x => {
+            eprintln!("Unhandled Wind Direction: {:?}", x);
+            ""
+        }
+    }
+}
+
+// minutes since the data was fetched, if it is older than a fresh cache would be
+pub fn stale_minutes(md: &Forecast, now: u64) -> Option<u64> {
+    let age = now.saturating_sub(md.current.time as u64);
+    (age >= CACHE_TIMEOUT).then_some(age / 60)
+}
+
+// warns above the tables when they are built from an outdated cache
+fn write_stale_notice(dst: &mut String, md: &Forecast, now: u64) {
+    if let Some(minutes) = stale_minutes(md, now) {
+        writeln!(
+            dst,
+            "Offline, showing forecast fetched {minutes} minutes ago"
+        )
+        .unwrap();
+    }
+}
+
+// in place of a table when the forecast has no samples in the window, e.g. a trimmed cache
+const NOTHING_TO_SHOW: &str = "No forecast samples for this time";
+
+// hourly weather info for the CLI
+pub fn hourly(md: &Forecast, opts: &RenderOptions, now: u64) -> String {
+    let color = opts.color;
+
+    // display collector
+    let mut dst = String::new();
+    write_stale_notice(&mut dst, md, now);
+
+    let time_data = &md.minutely_15.time;
+    if time_data.is_empty() {
+        writeln!(dst, "{NOTHING_TO_SHOW}").unwrap();
+        return dst;
+    }
+    let current_time_index = get_time_index(time_data, now);
+
+    let today = md.day_index(time_data[current_time_index]);
+
+    let range = opts
+        .window
+        .sample_range(current_time_index, md.minutely_15.time.len());
+    if range.is_empty() {
+        writeln!(dst, "{NOTHING_TO_SHOW}").unwrap();
+        return dst;
+    }
+    let (start, end) = (range.start, range.end);
+
+    let temp = &md.minutely_15.temperature_2m[start..end];
+
+    // high/low temp bar
+    let mut low: f64 = *temp
+        .iter()
+        .min_by(|a, b| a.partial_cmp(b).unwrap())
+        .unwrap();
+    let mut high: f64 = *temp
+        .iter()
+        .max_by(|a, b| a.partial_cmp(b).unwrap())
+        .unwrap();
+    {
+        // min margin between left/right sides of bar
+        let margin = 3.5;
+        // min gap between high/low after margin
+        let gap = 30.0;
+
+        low -= margin;
+        high += margin;
+
+        if high - low < gap {
+            let diff = gap - (high - low);
+            low -= diff / 2.0;
+            high += diff / 2.0;
+        }
+    }
+
+    let layout = layout(&opts.columns, opts.term_size.0, &opts.temperature);
+    let view = View {
+        opts,
+        bar_max: layout.bar,
+        temp_low: low,
+        temp_high: high,
+        sunrise: md.daily.sunrise.get(today).copied().unwrap_or_default(),
+        sunset: md.daily.sunset.get(today).copied().unwrap_or_default(),
+    };
+    let columns: Vec<(Spec, usize)> = layout.columns.iter().map(|(c, w)| (c.spec(), *w)).collect();
+
+    let mut header = String::from("  ");
+    for (spec, w) in &columns {
+        match spec.align {
+            Align::Left => write!(header, "{:<w$.w$} ", spec.header).unwrap(),
+            Align::Right => write!(header, "{:>w$.w$} ", spec.header).unwrap(),
+        }
+    }
+    writeln!(dst, "{}", header.trim_end()).unwrap();
+
+    for i in (start..end).step_by(hourly_res(opts)) {
+        // hour title
+        let default_fg_esc = if i == current_time_index {
+            let mut esc = String::new();
+            WHITE.write_fg_esc(&mut esc, color).unwrap();
+
+            PURPLE.write_bg_esc(&mut dst, color).unwrap();
+            write!(dst, "{esc}> ").unwrap();
+
+            esc
+        } else {
+            write!(dst, "  ").unwrap();
+
+            reset(color).to_string()
+        };
+
+        let cell = Cell { md, i, view: &view };
+        for (n, (spec, w)) in columns.iter().enumerate() {
+            match (spec.color)(&cell) {
+                Some(rgb) => rgb.write_fg_esc(&mut dst, color).unwrap(),
+                None => dst.push_str(&default_fg_esc),
+            }
+            let value = (spec.value)(&cell);
+            dst.push_str(&pad(&value, *w, &spec.align, opts.vs16));
+            if n + 1 < columns.len() {
+                dst.push(' ');
+            }
+        }
+
+        writeln!(dst, "{}", reset(color)).unwrap();
+    }
+    dst
+}
+
+// widths of the weekly table's sections
+const WEEK_DATE: usize = 11;
+const WEEK_TEMP: usize = 18;
+const WEEK_HUMIDITY: usize = 15;
+const WEEK_WET_BULB: usize = 18;
+const WEEK_WIND: usize = 9;
+const WEEK_UV: usize = 4;
+const WEEK_MIN_BAR: usize = 4;
+const WEEK_MAX_BAR: usize = 20;
+
+// which sections of the weekly table fit, the bar goes first, then wet bulb, humidity,
+// wind and uv. date and temperature always stay
+struct WeekLayout {
+    bar: Option<usize>,
+    humidity: bool,
+    wet_bulb: bool,
+    wind: bool,
+    uv: bool,
+}
+
+fn week_layout(width: usize) -> WeekLayout {
+    let avail = width.saturating_sub(1);
+    let mut layout = WeekLayout {
+        bar: None,
+        humidity: true,
+        wet_bulb: true,
+        wind: true,
+        uv: true,
+    };
+    let used = |l: &WeekLayout| {
+        WEEK_DATE
+            + WEEK_TEMP
+            + l.humidity as usize * WEEK_HUMIDITY
+            + l.wet_bulb as usize * WEEK_WET_BULB
+            + l.wind as usize * WEEK_WIND
+            + l.uv as usize * WEEK_UV
+    };
+
+    // the bar is padded by a space on either side
+    let room = avail.saturating_sub(used(&layout) + 2).min(WEEK_MAX_BAR);
+    if room >= WEEK_MIN_BAR {
+        layout.bar = Some(room);
+        return layout;
+    }
+    for drop in [
+        |l: &mut WeekLayout| l.wet_bulb = false,
+        |l: &mut WeekLayout| l.humidity = false,
+        |l: &mut WeekLayout| l.wind = false,
+        |l: &mut WeekLayout| l.uv = false,
+    ] {
+        if used(&layout) <= avail {
+            break;
+        }
+        drop(&mut layout);
+    }
+    layout
+}
+
+// weekly weather info for the CLI
+pub fn weekly(md: &Forecast, opts: &RenderOptions, now: u64) -> String {
+    let color = opts.color;
+    let scale = opts.temperature.scale;
+
+    let mut dst = String::new();
+    write_stale_notice(&mut dst, md, now);
+
+    let today = md.day_index(now as u32);
+    let days = md.days(now as u32);
+    let shown = opts.window.day_range(today, days.len());
+    let days = &days[shown.clone()];
+    if days.is_empty() {
+        writeln!(dst, "{NOTHING_TO_SHOW}").unwrap();
+        return dst;
+    }
+
+    let mut di: Vec<String> = vec![String::new(); days.len()];
+
+    // Date headers
+    for (i, start) in md.daily.time[shown.clone()].iter().enumerate() {
+        if shown.start + i == today {
+            write!(di[i], "> ").unwrap();
+        } else {
+            write!(di[i], "  ").unwrap();
+        }
+
+        let date = md.local_time(*start).date_naive();
+        // WHITE.write_fg_esc(&mut di[i]).unwrap();
+        write!(
+            di[i],
+            "{} {:>2}-{:<2}",
+            date.weekday(),
+            date.month(),
+            date.day()
+        )
+        .unwrap();
+    }
+
+    // Temperature data
+    let gl_min = days
+        .iter()
+        .map(|d| d.temperature.min)
+        .reduce(f64::min)
+        .unwrap();
+    let gl_max = days
+        .iter()
+        .map(|d| d.temperature.max)
+        .reduce(f64::max)
+        .unwrap();
+
+    let layout = week_layout(opts.term_size.0);
+
+    for (i, day) in days.iter().enumerate() {
+        let Stats { min, max, mean } = day.temperature;
+        let rgb_min = get_temp_rgb(min);
+        rgb_min.write_fg_esc(&mut di[i], color).unwrap();
+        write!(di[i], "{:>6.1}", scale.from_celsius(min)).unwrap();
+
+        let rgb_max = get_temp_rgb(max);
+        rgb_max.write_fg_esc(&mut di[i], color).unwrap();
+        write!(di[i], "{:->6.1}", scale.from_celsius(max)).unwrap();
+
+        let rgb_mean = get_temp_rgb(mean);
+        rgb_mean.write_fg_esc(&mut di[i], color).unwrap();
+        write!(di[i], "{:>6.1}", scale.from_celsius(mean)).unwrap();
+
+        if let Some(bar) = layout.bar {
+            let mean_bar = mk_bar(&mean, &gl_min, &gl_max, &1.0, bar);
+            write!(di[i], " {mean_bar:>bar$} ").unwrap();
+        }
+    }
+
+    // Humidity data
+    if layout.humidity {
+        for (i, day) in days.iter().enumerate() {
+            let Stats { min, max, mean } = day.relative_humidity;
+            let rgb_min = rgb_lerp(min, 30.0, 90.0, &WHITE, &DEEP_BLUE);
+            rgb_min.write_fg_esc(&mut di[i], color).unwrap();
+            write!(di[i], "{:>4.0}%", min).unwrap();
+
+            let rgb_max = rgb_lerp(max, 30.0, 90.0, &WHITE, &DEEP_BLUE);
+            rgb_max.write_fg_esc(&mut di[i], color).unwrap();
+            write!(di[i], "{:->4.0}%", max).unwrap();
+
+            let rgb_mean = rgb_lerp(mean, 30.0, 90.0, &WHITE, &DEEP_BLUE);
+            rgb_mean.write_fg_esc(&mut di[i], color).unwrap();
+            write!(di[i], "{:>4.0}%", mean).unwrap();
+        }
+    }
+
+    // Wet bulb temperature, blank for days without samples to compute it from
+    if layout.wet_bulb {
+        for (i, day) in days.iter().enumerate() {
+            let Some(Stats { min, max, mean }) = day.wet_bulb else {
+                write!(di[i], "{:WEEK_WET_BULB$}", "").unwrap();
+                continue;
+            };
+            let rgb_min = get_wb_rgb(min);
+            rgb_min.write_fg_esc(&mut di[i], color).unwrap();
+            write!(di[i], "{:>6.1}", scale.from_celsius(min)).unwrap();
+
+            let rgb_max = get_wb_rgb(max);
+            rgb_max.write_fg_esc(&mut di[i], color).unwrap();
+            write!(di[i], "{:->6.1}", scale.from_celsius(max)).unwrap();
+
+            let rgb_mean = get_wb_rgb(mean);
+            rgb_mean.write_fg_esc(&mut di[i], color).unwrap();
+            write!(di[i], "{:>6.1}", scale.from_celsius(mean)).unwrap();
+        }
+    }
+
+    // Wind speed data
+    if layout.wind {
+        for (i, day) in days.iter().enumerate() {
+            let Stats { min, max, mean } = day.wind_speed;
+            di[i].push_str(reset(color));
+            write!(di[i], "{:>3.0}", min).unwrap();
+            write!(di[i], "{:->3.0}", max).unwrap();
+            write!(di[i], "{:>3.0}", mean).unwrap();
+        }
+    }
+
+    // UV index
+    if layout.uv {
+        let uv_index = md.daily.uv_index_max.get(shown).unwrap_or_default();
+        for (i, uv) in uv_index.iter().enumerate() {
+            write!(di[i], "{} {:3.1}", reset(color), uv).unwrap();
+        }
+    }
+
+    let mut header = format!("  DAY  DATE{:>w$}", "TEMP", w = WEEK_TEMP);
+    if let Some(bar) = layout.bar {
+        write!(header, " {:<bar$.bar$} ", "TEMP-BAR").unwrap();
+    }
+    let sections = [
+        (layout.humidity, "HMT", WEEK_HUMIDITY),
+        (layout.wet_bulb, "WB", WEEK_WET_BULB),
+        (layout.wind, "WIND", WEEK_WIND),
+        (layout.uv, "UV", WEEK_UV),
+    ];
+    for (_, name, w) in sections.iter().filter(|(shown, ..)| *shown) {
+        write!(header, "{name:>w$}").unwrap();
+    }
+    writeln!(dst, "{}", header.trim_end()).unwrap();
+    for line in di.into_iter() {
+        writeln!(dst, "{line}{}", reset(color)).unwrap();
+    }
+    dst
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forecast::{Daily, Series};
+    use crate::provider::fixture;
+    use crate::width::display_width;
+    use crate::Vs16;
+    use crate::Window;
+
+    // 09:07 in Chicago on the fixture's first day
+    const NOW: u64 = 1792246020;
+
+    fn table(view: fn(&Forecast, &RenderOptions, u64) -> String, width: usize) -> Vec<String> {
+        let opts = RenderOptions {
+            color: false,
+            term_size: (width, 60),
+            ..RenderOptions::default()
+        };
+        let output = view(&fixture(), &opts, NOW);
+        // plain output has no escapes at all
+        assert!(!output.contains('\x1b'), "{output:?}");
+        output.lines().map(|l| l.trim_end().to_string()).collect()
+    }
+
+    fn assert_fits(lines: &[String], width: usize) {
+        for line in lines {
+            assert!(
+                display_width(line, Vs16::Wide) < width,
+                "{line:?} at {width}"
+            );
+        }
+    }
+
+    #[test]
+    fn hourly_at_widths() {
+        let cases = [
+            (
+                120,
+                "  TIME   TEMP TEMP-BAR                  HMT    WB PRCP PRCP-BAR                  WIND WMO",
+                "> 9am   11.0° ████████████              68%   7.7   0%                          10 SW ☀\u{fe0f} Clear",
+            ),
+            (
+                80,
+                "  TIME   TEMP TEMP-BAR      HMT    WB PRCP PRCP-BAR      WIND WMO",
+                "> 9am   11.0° ██████        68%   7.7   0%              10 SW ☀\u{fe0f} Clear",
+            ),
+            (
+                60,
+                "  TIME   TEMP TEMP-B  HMT    WB PRCP  WIND WMO",
+                "> 9am   11.0° ███     68%   7.7   0% 10 SW ☀\u{fe0f} Clear",
+            ),
+            (
+                40,
+                "  TIME   TEMP PRCP  WIND WMO",
+                "> 9am   11.0°   0% 10 SW ☀\u{fe0f} Clear",
+            ),
+        ];
+        for (width, header, current) in cases {
+            let lines = table(hourly, width);
+            assert_eq!(lines[0], header);
+            assert_eq!(lines.iter().find(|l| l.starts_with('>')).unwrap(), current);
+            assert_fits(&lines, width);
+        }
+    }
+
+    #[test]
+    fn weekly_at_widths() {
+        let cases = [
+            (
+                120,
+                "  DAY  DATE              TEMP TEMP-BAR                         HMT                WB     WIND  UV",
+                "> Sat 10-17   5.0--17.0  11.0 ██████████             56%--80%  68%   3.1--11.8   7.6  4-10  8 3.5",
+            ),
+            (
+                80,
+                "  DAY  DATE              TEMP            HMT                WB     WIND  UV",
+                "> Sat 10-17   5.0--17.0  11.0  56%--80%  68%   3.1--11.8   7.6  4-10  8 3.5",
+            ),
+            (
+                60,
+                "  DAY  DATE              TEMP            HMT     WIND  UV",
+                "> Sat 10-17   5.0--17.0  11.0  56%--80%  68%  4-10  8 3.5",
+            ),
+            (
+                40,
+                "  DAY  DATE              TEMP  UV",
+                "> Sat 10-17   5.0--17.0  11.0 3.5",
+            ),
+        ];
+        for (width, header, today) in cases {
+            let lines = table(weekly, width);
+            assert_eq!(lines[0], header);
+            assert_eq!(lines[1], today);
+            assert_fits(&lines, width);
+        }
+    }
+
+    #[test]
+    fn empty_forecast() {
+        let opts = RenderOptions {
+            color: false,
+            ..RenderOptions::default()
+        };
+        let mut md = fixture();
+        md.minutely_15 = Series::default();
+        md.hourly = Series::default();
+        md.daily = Daily::default();
+        assert_eq!(hourly(&md, &opts, NOW), format!("{NOTHING_TO_SHOW}\n"));
+        assert_eq!(weekly(&md, &opts, NOW), format!("{NOTHING_TO_SHOW}\n"));
+
+        // samples, but none in the window
+        let opts = RenderOptions {
+            window: Window {
+                hours_back: 0,
+                hours_ahead: 0,
+                days: 0,
+                past_days: 0,
+            },
+            ..opts
+        };
+        let md = fixture();
+        assert_eq!(hourly(&md, &opts, NOW), format!("{NOTHING_TO_SHOW}\n"));
+        assert_eq!(weekly(&md, &opts, NOW), format!("{NOTHING_TO_SHOW}\n"));
+    }
+
+    #[test]
+    fn time_index() {
+        let time = [1000, 1900, 2800, 3700];
+        assert_eq!(get_time_index(&time, 1000), 0);
+        assert_eq!(get_time_index(&time, 2799), 1);
+        assert_eq!(get_time_index(&time, 2800), 2);
+        // before the first sample and past the last one
+        assert_eq!(get_time_index(&time, 10), 0);
+        assert_eq!(get_time_index(&time, 3700 + 86400), 3);
+        assert_eq!(get_time_index(&[], 1000), 0);
+    }
+
+    // an old cache shows its last sample as the current conditions, marked with its age
+    #[test]
+    fn past_the_forecast() {
+        let md = fixture();
+        let last = *md.minutely_15.time.last().unwrap() as u64;
+        let now = last + 3 * 3600;
+        let opts = RenderOptions {
+            color: false,
+            ..RenderOptions::default()
+        };
+        let c = current_conditions(&md, &opts, now);
+        assert_eq!(c.temp, *md.minutely_15.temperature_2m.last().unwrap());
+        let age = (now - md.current.time as u64) / 60;
+        assert_eq!(c.stale, Some(age));
+        assert!(one_line(&md, &opts, now).ends_with(&format!("({age}m old)\n")));
+        let table = hourly(&md, &opts, now);
+        assert!(table.starts_with(&format!(
+            "Offline, showing forecast fetched {age} minutes ago\n"
+        )));
+        assert!(table.lines().last().unwrap().starts_with("> "));
+    }
+}
@@ -1,14 +1,68 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
 
+// failed lookups only carry status and message
 #[derive(Serialize, Deserialize, Debug)]
 pub struct IpApiResponse {
     pub status: String,
+    pub message: Option<String>,
+    #[serde(default)]
     pub lat: f64,
+    #[serde(default)]
     pub lon: f64,
+    #[serde(default)]
     pub timezone: String,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IpinfoResponse {
+    // "41.8500,-87.6500", missing for bogon addresses
+    pub loc: Option<String>,
+    pub timezone: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IfconfigResponse {
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub time_zone: Option<String>,
+}
+
+// what open-meteo's nulls become, it sends them where a model has no value, e.g. past
+// the end of the 15 minute models or for precipitation chances in some regions
+trait Missing {
+    const MISSING: Self;
+}
+
+impl Missing for f64 {
+    const MISSING: f64 = f64::NAN;
+}
+
+// no wmo code has this value, it renders as unknown
+impl Missing for u8 {
+    const MISSING: u8 = u8::MAX;
+}
+
+impl Missing for i16 {
+    const MISSING: i16 = 0;
+}
+
+impl Missing for i32 {
+    const MISSING: i32 = 0;
+}
+
+fn nullable<'de, D, T>(d: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Missing,
+{
+    let values: Vec<Option<T>> = Vec::deserialize(d)?;
+    Ok(values
+        .into_iter()
+        .map(|v| v.unwrap_or(T::MISSING))
+        .collect())
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct MeteoApiResponse {
     pub latitude: f64,
@@ -20,25 +74,15 @@ pub struct MeteoApiResponse {
     pub elevation: f64,
     pub current_units: HashMap<String, String>,
     pub current: CurrentData,
-    pub hourly_units: HourlyUnits,
+    // absent when not requested
+    #[serde(default)]
     pub hourly: HourlyData,
+    #[serde(default)]
     pub minutely_15: FifteenMinutely,
     pub daily_units: HashMap<String, String>,
     pub daily: DailyData,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct HourlyUnits {
-    pub time: String,
-    pub relative_humidity_2m: String,
-    pub precipitation_probability: String,
-    pub dew_point_2m: String,
-    pub wind_speed_10m: String,
-    pub wind_direction_10m: String,
-    pub temperature_2m: String,
-    pub weather_code: String,
-}
-
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CurrentData {
     pub time: u32,
@@ -48,41 +92,84 @@ pub struct CurrentData {
     pub weather_code: u8,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct HourlyData {
     pub time: Vec<u32>,
+    #[serde(deserialize_with = "nullable")]
+    pub pressure_msl: Vec<f64>,
+    #[serde(deserialize_with = "nullable")]
     pub temperature_2m: Vec<f64>,
+    #[serde(deserialize_with = "nullable")]
     pub relative_humidity_2m: Vec<f64>,
+    #[serde(deserialize_with = "nullable")]
     pub dew_point_2m: Vec<f64>,
+    #[serde(deserialize_with = "nullable")]
+    pub apparent_temperature: Vec<f64>,
+    #[serde(deserialize_with = "nullable")]
+    pub precipitation: Vec<f64>,
+    #[serde(deserialize_with = "nullable")]
     pub precipitation_probability: Vec<f64>,
+    #[serde(deserialize_with = "nullable")]
     pub weather_code: Vec<u8>,
+    #[serde(deserialize_with = "nullable")]
     pub wind_speed_10m: Vec<f64>,
+    #[serde(deserialize_with = "nullable")]
     pub wind_direction_10m: Vec<i16>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct FifteenMinutely {
     pub time: Vec<u32>,
+    #[serde(deserialize_with = "nullable")]
     pub temperature_2m: Vec<f64>,
+    #[serde(deserialize_with = "nullable")]
     pub relative_humidity_2m: Vec<f64>,
+    #[serde(deserialize_with = "nullable")]
     pub dew_point_2m: Vec<f64>,
+    #[serde(deserialize_with = "nullable")]
+    pub apparent_temperature: Vec<f64>,
+    #[serde(deserialize_with = "nullable")]
+    pub precipitation: Vec<f64>,
+    #[serde(deserialize_with = "nullable")]
     pub precipitation_probability: Vec<f64>,
+    #[serde(deserialize_with = "nullable")]
     pub weather_code: Vec<u8>,
+    #[serde(deserialize_with = "nullable")]
     pub wind_speed_10m: Vec<f64>,
+    #[serde(deserialize_with = "nullable")]
     pub wind_direction_10m: Vec<i16>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct DailyData {
     pub time: Vec<u32>,
+    #[serde(deserialize_with = "nullable")]
     pub temperature_2m_max: Vec<f64>,
+    #[serde(deserialize_with = "nullable")]
     pub temperature_2m_min: Vec<f64>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub temperature_2m_mean: Vec<f64>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub relative_humidity_2m_max: Vec<f64>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub relative_humidity_2m_min: Vec<f64>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub relative_humidity_2m_mean: Vec<f64>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub wind_speed_10m_min: Vec<f64>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub wind_speed_10m_mean: Vec<f64>,
     pub sunrise: Vec<u32>,
     pub sunset: Vec<u32>,
+    #[serde(deserialize_with = "nullable")]
     pub precipitation_probability_max: Vec<i32>,
+    #[serde(deserialize_with = "nullable")]
     pub wind_speed_10m_max: Vec<f64>,
+    #[serde(deserialize_with = "nullable")]
     pub weather_code: Vec<u8>,
+    #[serde(deserialize_with = "nullable")]
     pub uv_index_max: Vec<f64>,
+    #[serde(deserialize_with = "nullable")]
     pub uv_index_clear_sky_max: Vec<f64>,
 }
 
@@ -91,7 +178,7 @@ pub struct GeocodingResponse {
     pub results: Option<Vec<GeocodingResult>>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GeocodingResult {
     pub name: String,
     pub latitude: f64,
@@ -99,4 +186,72 @@ pub struct GeocodingResult {
     pub timezone: String,
     pub country: Option<String>,
     pub admin1: Option<String>,
+    #[serde(default)]
+    pub country_code: Option<String>,
+    #[serde(default)]
+    pub population: Option<u64>,
+}
+
+// api.met.no locationforecast/2.0/complete, only the fields we use
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MetResponse {
+    pub geometry: MetGeometry,
+    pub properties: MetProperties,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MetGeometry {
+    // lon, lat, altitude
+    pub coordinates: Vec<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MetProperties {
+    pub timeseries: Vec<MetTimestep>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MetTimestep {
+    pub time: String,
+    pub data: MetData,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MetData {
+    pub instant: MetInstant,
+    pub next_1_hours: Option<MetPeriod>,
+    pub next_6_hours: Option<MetPeriod>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MetInstant {
+    pub details: MetInstantDetails,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MetInstantDetails {
+    pub air_temperature: f64,
+    pub air_pressure_at_sea_level: Option<f64>,
+    pub relative_humidity: f64,
+    pub dew_point_temperature: Option<f64>,
+    pub wind_speed: f64,
+    pub wind_from_direction: f64,
+    pub ultraviolet_index_clear_sky: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MetPeriod {
+    pub summary: MetSummary,
+    pub details: Option<MetPeriodDetails>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MetSummary {
+    pub symbol_code: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MetPeriodDetails {
+    pub probability_of_precipitation: Option<f64>,
+    pub precipitation_amount: Option<f64>,
 }
@@ -0,0 +1,352 @@
+// user defined one line output in the spirit of wttr.in's format codes
+//
+//   %t temperature   %h humidity       %b wet bulb      %w wind speed   %W wind direction
+//   %c weather icon  %C weather text   %p precipitation chance          %m moon phase
+//   %S sunrise       %s sunset         %a minutes since the forecast was fetched
+//
+// `%*x` colors a field like the tables do and `%{#rrggbb}x` with a fixed color. text inside
+// `[...]` is only shown when every field in it has a value: %p needs a chance of precipitation,
+// %W some wind and %a a stale forecast. `%%`, `%[` and `%]` are literal
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::str::FromStr;
+
+use crate::color::{get_temp_rgb, get_wb_rgb, rgb_lerp, Rgb, DEEP_BLUE, ICE_BLUE, WHITE};
+use crate::render::Conditions;
+
+// how field colors are written
+#[derive(Clone, Copy, Debug)]
+pub enum Style {
+    Plain,
+    Ansi,
+    // polybar/lemonbar %{F} tags
+    Polybar,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Field {
+    Temp,
+    Humidity,
+    WetBulb,
+    WindSpeed,
+    WindDirection,
+    Icon,
+    Text,
+    Precip,
+    Sunrise,
+    Sunset,
+    Moon,
+    Age,
+}
+
+#[derive(Clone, Debug)]
+enum Paint {
+    None,
+    Auto,
+    Fixed(Rgb),
+}
+
+#[derive(Clone, Debug)]
+enum Part {
+    Literal(String),
+    Field(Field, Paint),
+    Section(Vec<Part>),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Field {
+    fn from_char(c: char) -> Result<Field> {
+        Ok(match c {
+            't' => Field::Temp,
+            'h' => Field::Humidity,
+            'b' => Field::WetBulb,
+            'w' => Field::WindSpeed,
+            'W' => Field::WindDirection,
+            'c' => Field::Icon,
+            'C' => Field::Text,
+            'p' => Field::Precip,
+            'S' => Field::Sunrise,
+            's' => Field::Sunset,
+            'm' => Field::Moon,
+            'a' => Field::Age,
+            c => return Err(anyhow!("unknown placeholder %{c}")),
+        })
+    }
+
+    // None when there is nothing worth showing, which hides the enclosing section
+    fn value(&self, c: &Conditions) -> Option<String> {
+        match self {
+            Field::Temp => Some(c.temperature.format(c.temp)),
+            Field::Humidity => Some(format!("{}%", c.humid)),
+            Field::WetBulb => {
+                let scale = c.temperature.scale;
+                let wet_bulb = scale.from_celsius(c.wet_bulb);
+                Some(format!("{wet_bulb:.1}{}", scale.symbol()))
+            }
+            Field::WindSpeed => Some(format!("{:.0} {}", c.wind_speed, c.wind_unit)),
+            Field::WindDirection => (c.wind_speed > 0.0).then(|| c.wind_direction.to_string()),
+            Field::Icon => Some(c.wmo_string.clone()),
+            Field::Text => Some(crate::wmo::text(c.wmo).to_string()),
+            Field::Precip => (c.precip_max > 0).then(|| format!("{}%", c.precip_max)),
+            Field::Sunrise => Some(c.sunrise.clone()),
+            Field::Sunset => Some(c.sunset.clone()),
+            Field::Moon => Some(c.moon.emoji().to_string()),
+            Field::Age => c.stale.map(|minutes| format!("{minutes}m")),
+        }
+    }
+
+    // the color the tables use for this value
+    fn color(&self, c: &Conditions) -> Rgb {
+        match self {
+            Field::Temp => get_temp_rgb(c.temp),
+            Field::Humidity => rgb_lerp(c.humid, 30.0, 90.0, &WHITE, &DEEP_BLUE),
+            Field::WetBulb => get_wb_rgb(c.wet_bulb),
+            Field::Icon | Field::Text => c.wmo_rgb.clone(),
+            Field::Precip => rgb_lerp(c.precip_max as f64, 0.0, 100.0, &ICE_BLUE, &DEEP_BLUE),
+            _ => WHITE,
+        }
+    }
+}
+
+impl FromStr for Template {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        // the top level, plus the open section if inside one
+        let mut stack: Vec<Vec<Part>> = vec![vec![]];
+        let mut literal = String::new();
+        let flush = |stack: &mut Vec<Vec<Part>>, literal: &mut String| {
+            if !literal.is_empty() {
+                let top = stack.last_mut().unwrap();
+                top.push(Part::Literal(std::mem::take(literal)));
+            }
+        };
+
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '%' => {
+                    let paint = match chars.next() {
+                        Some(c @ ('%' | '[' | ']')) => {
+                            literal.push(c);
+                            continue;
+                        }
+                        Some('*') => Paint::Auto,
+                        Some('{') => {
+                            let spec: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                            let rgb = Rgb::from_hex(&spec).ok_or_else(|| {
+                                anyhow!("invalid color \"{spec}\", expected #rrggbb")
+                            })?;
+                            Paint::Fixed(rgb)
+                        }
+                        Some(c) => {
+                            flush(&mut stack, &mut literal);
+                            let top = stack.last_mut().unwrap();
+                            top.push(Part::Field(Field::from_char(c)?, Paint::None));
+                            continue;
+                        }
+                        None => return Err(anyhow!("template ends with a lone %")),
+                    };
+                    let field = chars
+                        .next()
+                        .ok_or_else(|| anyhow!("template ends before the colored placeholder"))?;
+                    flush(&mut stack, &mut literal);
+                    let top = stack.last_mut().unwrap();
+                    top.push(Part::Field(Field::from_char(field)?, paint));
+                }
+                '[' => {
+                    if stack.len() > 1 {
+                        return Err(anyhow!("nested [sections] are not supported"));
+                    }
+                    flush(&mut stack, &mut literal);
+                    stack.push(vec![]);
+                }
+                ']' => {
+                    if stack.len() == 1 {
+                        return Err(anyhow!("unmatched ]"));
+                    }
+                    flush(&mut stack, &mut literal);
+                    let section = stack.pop().unwrap();
+                    stack.last_mut().unwrap().push(Part::Section(section));
+                }
+                c => literal.push(c),
+            }
+        }
+        if stack.len() > 1 {
+            return Err(anyhow!("unclosed ["));
+        }
+        flush(&mut stack, &mut literal);
+
+        Ok(Template {
+            parts: stack.pop().unwrap(),
+        })
+    }
+}
+
+impl TryFrom<String> for Template {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+// appends `parts` to dst, false if a field had no value
+fn render_parts(dst: &mut String, parts: &[Part], c: &Conditions, style: Style) -> bool {
+    let mut complete = true;
+    for part in parts {
+        match part {
+            Part::Literal(s) => dst.push_str(s),
+            Part::Field(field, paint) => {
+                let Some(value) = field.value(c) else {
+                    complete = false;
+                    continue;
+                };
+                let rgb = match paint {
+                    Paint::None => None,
+                    Paint::Auto => Some(field.color(c)),
+                    Paint::Fixed(rgb) => Some(rgb.clone()),
+                };
+                match (rgb, style) {
+                    (Some(rgb), Style::Ansi) => {
+                        rgb.write_fg_esc(dst, true).unwrap();
+                        dst.push_str(&value);
+                        dst.push_str("\x1b[0m");
+                    }
+                    (Some(rgb), Style::Polybar) => {
+                        dst.push_str(&format!("%{{F{}}}{value}%{{F-}}", rgb.hex()));
+                    }
+                    _ => dst.push_str(&value),
+                }
+            }
+            Part::Section(parts) => {
+                let mut section = String::new();
+                if render_parts(&mut section, parts, c, style) {
+                    dst.push_str(&section);
+                }
+            }
+        }
+    }
+    complete
+}
+
+impl Template {
+    pub fn render(&self, c: &Conditions, style: Style) -> String {
+        let mut dst = String::new();
+        render_parts(&mut dst, &self.parts, c, style);
+        dst
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::fixture;
+    use crate::render::current_conditions;
+    use crate::RenderOptions;
+
+    // 09:07 on the fixture's first day: 11°, 68%, wind from the southwest
+    fn conditions() -> Conditions {
+        current_conditions(&fixture(), &RenderOptions::default(), 1792246020)
+    }
+
+    fn render(template: &str, c: &Conditions, style: Style) -> String {
+        template.parse::<Template>().unwrap().render(c, style)
+    }
+
+    fn error(template: &str) -> String {
+        template.parse::<Template>().unwrap_err().to_string()
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(error("%t %"), "template ends with a lone %");
+        assert_eq!(error("%x"), "unknown placeholder %x");
+        assert_eq!(error("%*q"), "unknown placeholder %q");
+        assert_eq!(error("%t]"), "unmatched ]");
+        assert_eq!(error("[%t"), "unclosed [");
+        assert_eq!(error("[a[%t]]"), "nested [sections] are not supported");
+        assert_eq!(error("%*"), "template ends before the colored placeholder");
+        assert_eq!(
+            error("%{#ff88}t"),
+            "invalid color \"#ff88\", expected #rrggbb"
+        );
+        assert_eq!(
+            error("%{orange}t"),
+            "invalid color \"orange\", expected #rrggbb"
+        );
+    }
+
+    #[test]
+    fn fields() {
+        let c = conditions();
+        assert_eq!(render("%t %h %C", &c, Style::Plain), "11° 68% Clear sky");
+        assert_eq!(render("%% %[%t%] 100%%", &c, Style::Plain), "% [11°] 100%");
+        assert_eq!(render("%w %W", &c, Style::Plain), "10 mp/h SW");
+    }
+
+    #[test]
+    fn wind_speed_precision() {
+        // resampled MET Norway values aren't round
+        let c = Conditions {
+            wind_speed: 7.249999999,
+            ..conditions()
+        };
+        assert_eq!(render("%w", &c, Style::Plain), "7 mp/h");
+    }
+
+    #[test]
+    fn sections() {
+        let c = Conditions {
+            precip_max: 0,
+            wind_speed: 0.0,
+            stale: None,
+            ..conditions()
+        };
+        assert_eq!(render("%t[ ~%p][ %W][ (%a old)]", &c, Style::Plain), "11°");
+        let c = Conditions {
+            precip_max: 40,
+            wind_speed: 12.0,
+            stale: Some(90),
+            ..conditions()
+        };
+        assert_eq!(
+            render("%t[ ~%p][ %W][ (%a old)]", &c, Style::Plain),
+            "11° ~40% SW (90m old)"
+        );
+        // one missing field hides its whole section, and only that
+        let c = Conditions { precip_max: 0, ..c };
+        assert_eq!(render("[%t ~%p] [%h]", &c, Style::Plain), " 68%");
+    }
+
+    #[test]
+    fn styles() {
+        let c = conditions();
+        let rgb = get_temp_rgb(c.temp);
+        let mut ansi = String::new();
+        rgb.write_fg_esc(&mut ansi, true).unwrap();
+        assert_eq!(render("%*t", &c, Style::Ansi), format!("{ansi}11°\x1b[0m"));
+        assert_eq!(
+            render("%*t", &c, Style::Polybar),
+            format!("%{{F{}}}11°%{{F-}}", rgb.hex())
+        );
+        assert_eq!(render("%*t", &c, Style::Plain), "11°");
+
+        assert_eq!(
+            render("%{#ff8800}h!", &c, Style::Ansi),
+            "\x1b[38;2;255;136;0m68%\x1b[0m!"
+        );
+        assert_eq!(
+            render("%{#ff8800}h!", &c, Style::Polybar),
+            "%{F#ff8800}68%%{F-}!"
+        );
+        assert_eq!(render("%{#ff8800}h!", &c, Style::Plain), "68%!");
+        // uncolored fields are plain in every style
+        assert_eq!(render("%h", &c, Style::Ansi), "68%");
+    }
+}
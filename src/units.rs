@@ -0,0 +1,237 @@
+// units forecasts are fetched and shown in. wind, precipitation and pressure are converted
+// once when fetching so everything downstream of a Forecast just prints what it has,
+// temperatures are kept in °C and converted as they are shown
+use serde::{Deserialize, Serialize};
+
+use crate::TempScale;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WindUnit {
+    Kmh,
+    Ms,
+    Mph,
+    Kn,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PrecipUnit {
+    Mm,
+    Inch,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PressureUnit {
+    Hpa,
+    Inhg,
+    Mmhg,
+}
+
+#[derive(Clone, Copy, Debug, clap::ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Preset {
+    Metric,
+    Imperial,
+}
+
+impl WindUnit {
+    // the value open-meteo's wind_speed_unit takes
+    pub fn key(&self) -> &'static str {
+        match self {
+            WindUnit::Kmh => "kmh",
+            WindUnit::Ms => "ms",
+            WindUnit::Mph => "mph",
+            WindUnit::Kn => "kn",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            WindUnit::Kmh => "km/h",
+            WindUnit::Ms => "m/s",
+            WindUnit::Mph => "mp/h",
+            WindUnit::Kn => "kn",
+        }
+    }
+
+    pub fn from_ms(&self, ms: f64) -> f64 {
+        match self {
+            WindUnit::Kmh => ms * 3.6,
+            WindUnit::Ms => ms,
+            WindUnit::Mph => ms * 2.236936,
+            WindUnit::Kn => ms * 1.943844,
+        }
+    }
+}
+
+impl PrecipUnit {
+    // the value open-meteo's precipitation_unit takes
+    pub fn key(&self) -> &'static str {
+        match self {
+            PrecipUnit::Mm => "mm",
+            PrecipUnit::Inch => "inch",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PrecipUnit::Mm => "mm",
+            PrecipUnit::Inch => "inch",
+        }
+    }
+
+    pub fn from_mm(&self, mm: f64) -> f64 {
+        match self {
+            PrecipUnit::Mm => mm,
+            PrecipUnit::Inch => mm / 25.4,
+        }
+    }
+}
+
+impl PressureUnit {
+    pub fn key(&self) -> &'static str {
+        match self {
+            PressureUnit::Hpa => "hpa",
+            PressureUnit::Inhg => "inhg",
+            PressureUnit::Mmhg => "mmhg",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PressureUnit::Hpa => "hPa",
+            PressureUnit::Inhg => "inHg",
+            PressureUnit::Mmhg => "mmHg",
+        }
+    }
+
+    pub fn from_hpa(&self, hpa: f64) -> f64 {
+        match self {
+            PressureUnit::Hpa => hpa,
+            PressureUnit::Inhg => hpa * 0.02953,
+            PressureUnit::Mmhg => hpa * 0.750062,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnitSystem {
+    pub wind: WindUnit,
+    pub precipitation: PrecipUnit,
+    pub pressure: PressureUnit,
+}
+
+impl Default for UnitSystem {
+    // what weather-rs has always shown
+    fn default() -> Self {
+        UnitSystem {
+            wind: WindUnit::Mph,
+            precipitation: PrecipUnit::Mm,
+            pressure: PressureUnit::Hpa,
+        }
+    }
+}
+
+impl Preset {
+    pub fn temperature(&self) -> TempScale {
+        match self {
+            Preset::Metric => TempScale::Celsius,
+            Preset::Imperial => TempScale::Fahrenheit,
+        }
+    }
+}
+
+impl From<Preset> for UnitSystem {
+    fn from(preset: Preset) -> Self {
+        match preset {
+            Preset::Metric => UnitSystem {
+                wind: WindUnit::Kmh,
+                precipitation: PrecipUnit::Mm,
+                pressure: PressureUnit::Hpa,
+            },
+            Preset::Imperial => UnitSystem {
+                wind: WindUnit::Mph,
+                precipitation: PrecipUnit::Inch,
+                pressure: PressureUnit::Inhg,
+            },
+        }
+    }
+}
+
+impl UnitSystem {
+    // identifies the unit system a forecast was requested in, part of the cache key
+    pub fn key(&self) -> String {
+        format!(
+            "{}_{}_{}",
+            self.wind.key(),
+            self.precipitation.key(),
+            self.pressure.key()
+        )
+    }
+}
+
+// how temperatures are shown, `also` follows the first unit with a second, e.g. 72°F/22°C
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TempDisplay {
+    pub scale: TempScale,
+    pub also: Option<TempScale>,
+}
+
+impl Default for TempDisplay {
+    fn default() -> Self {
+        TempDisplay {
+            scale: TempScale::Celsius,
+            also: None,
+        }
+    }
+}
+
+// rounded to a tenth like the providers' values, so they print the same way
+fn tenths(x: f64) -> f64 {
+    (x * 10.0).round() / 10.0
+}
+
+impl TempDisplay {
+    // `celsius` in the main unit
+    pub fn convert(&self, celsius: f64) -> f64 {
+        tenths(self.scale.from_celsius(celsius))
+    }
+
+    // as short as the value allows, e.g. "9.4°" or "49°F/9.4°C" with a second unit
+    pub fn format(&self, celsius: f64) -> String {
+        match self.also {
+            None => format!("{}{}", self.convert(celsius), self.scale.symbol()),
+            Some(also) => format!(
+                "{}{}/{}{}",
+                self.convert(celsius),
+                self.scale.label(),
+                tenths(also.from_celsius(celsius)),
+                also.label()
+            ),
+        }
+    }
+
+    // a table cell, one decimal alone and whole degrees when both units share it
+    pub fn cell(&self, celsius: f64) -> String {
+        match self.also {
+            None => format!("{:5.1}{}", self.convert(celsius), self.scale.symbol()),
+            Some(also) => format!(
+                "{:.0}{}/{:.0}{}",
+                self.scale.from_celsius(celsius),
+                self.scale.label(),
+                also.from_celsius(celsius),
+                also.label()
+            ),
+        }
+    }
+
+    // characters cell() needs, "-10°F/-23°C" with a second unit
+    pub fn cell_width(&self) -> usize {
+        match self.also {
+            None => 6,
+            Some(_) => 11,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+// display width of table cells, emoji take two columns and some terminals disagree on how
+// wide a character followed by VS16 (U+FE0F, "show as emoji") is
+use unicode_width::UnicodeWidthChar;
+
+use crate::Vs16;
+
+const VS16: char = '\u{fe0f}';
+
+// width of each char in `s` as drawn, a VS16 is folded into the char before it
+fn char_widths(s: &str, vs16: Vs16) -> impl Iterator<Item = (&str, usize)> {
+    let mut rest = s;
+    std::iter::from_fn(move || {
+        let mut chars = rest.char_indices();
+        let (_, c) = chars.next()?;
+        let base = c.width().unwrap_or(0);
+        let (len, width) = match chars.next() {
+            Some((i, VS16)) => (i + VS16.len_utf8(), vs16.width(base)),
+            Some((i, _)) => (i, base),
+            None => (rest.len(), base),
+        };
+        let (part, tail) = rest.split_at(len);
+        rest = tail;
+        Some((part, width))
+    })
+}
+
+pub fn display_width(s: &str, vs16: Vs16) -> usize {
+    char_widths(s, vs16).map(|(_, w)| w).sum()
+}
+
+pub enum Align {
+    Left,
+    Right,
+}
+
+// `s` cut and padded with spaces to exactly `width` columns, a wide char that would
+// straddle the edge is replaced by a space
+pub fn pad(s: &str, width: usize, align: &Align, vs16: Vs16) -> String {
+    let mut out = String::new();
+    let mut used = 0;
+    for (part, w) in char_widths(s, vs16) {
+        if used + w > width {
+            break;
+        }
+        out.push_str(part);
+        used += w;
+    }
+    let fill = " ".repeat(width - used);
+    match align {
+        Align::Left => out + &fill,
+        Align::Right => fill + &out,
+    }
+}
@@ -0,0 +1,463 @@
+// WMO weather interpretation codes as documented by open-meteo, and how each is shown
+use serde::Serialize;
+
+use crate::color::{Rgb, ALT_YELLOW, CLEAR_BLUE, DEEP_BLUE, ICE_BLUE, L_GRAY, MID_BLUE, YELLOW};
+use crate::width::display_width;
+use crate::{EmojiMode, Vs16};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WmoCode {
+    ClearSky = 0,
+    MainlyClear = 1,
+    PartlyCloudy = 2,
+    Overcast = 3,
+    Fog = 45,
+    RimeFog = 48,
+    DrizzleLight = 51,
+    DrizzleModerate = 53,
+    DrizzleDense = 55,
+    FreezingDrizzleLight = 56,
+    FreezingDrizzleDense = 57,
+    RainSlight = 61,
+    RainModerate = 63,
+    RainHeavy = 65,
+    FreezingRainLight = 66,
+    FreezingRainHeavy = 67,
+    SnowSlight = 71,
+    SnowModerate = 73,
+    SnowHeavy = 75,
+    SnowGrains = 77,
+    RainShowersSlight = 80,
+    RainShowersModerate = 81,
+    RainShowersViolent = 82,
+    SnowShowersSlight = 85,
+    SnowShowersHeavy = 86,
+    Thunderstorm = 95,
+    ThunderstormSlightHail = 96,
+    ThunderstormHeavyHail = 99,
+}
+
+// how much the weather gets in the way, ice and hail rank above heavy rain
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    None,
+    Light,
+    Moderate,
+    Heavy,
+    Dangerous,
+}
+
+// labels are "icon text", "%m" is replaced by the moon phase and "%i" by its illumination
+struct Entry {
+    code: WmoCode,
+    text: &'static str,
+    severity: Severity,
+    // (day, night)
+    color: (&'static Rgb, &'static Rgb),
+    nerd_font: &'static str,
+    original: (&'static str, &'static str),
+    technical: (&'static str, &'static str),
+}
+
+// ⛈️ 🌩️
+// 🌥️⛅🌤️
+// ☁️ 🌧️🌨️🌦️
+// 🌫️❄️ ☀️ 🔅🔆
+// ☔️🌪️ 🌇🌆🏙️🌃⛆
+// 🌕🌖🌗🌘🌑🌒🌓🌔
+const TABLE: [Entry; 28] = [
+    Entry {
+        code: WmoCode::ClearSky,
+        text: "Clear sky",
+        severity: Severity::None,
+        color: (&ALT_YELLOW, &CLEAR_BLUE),
+        nerd_font: "\u{e33d} ~Clear",
+        original: ("☀️ Clear", "🌒 Clear"),
+        technical: ("☀️ Clear", "%m Clear %i"),
+    },
+    Entry {
+        code: WmoCode::MainlyClear,
+        text: "Mainly clear",
+        severity: Severity::None,
+        color: (&ALT_YELLOW, &CLEAR_BLUE),
+        nerd_font: "\u{e33d} <Clear",
+        original: ("🌇 Clear~", "🌃 Clear~"),
+        technical: ("🌤️ Clear", "%m Clear %i"),
+    },
+    Entry {
+        code: WmoCode::PartlyCloudy,
+        text: "Partly cloudy",
+        severity: Severity::None,
+        color: (&L_GRAY, &L_GRAY),
+        nerd_font: "\u{e312} ~Cloudy",
+        original: ("⛅ Cloudy~", "☁️ Cloudy~"),
+        technical: ("🏙️ Cloudy", "🌃 Cloudy"),
+    },
+    Entry {
+        code: WmoCode::Overcast,
+        text: "Overcast",
+        severity: Severity::None,
+        color: (&L_GRAY, &L_GRAY),
+        nerd_font: "\u{e312} >Cloudy",
+        original: ("☁️ Cloudy", "☁️ Cloudy"),
+        technical: ("☁️ Cloudy", "☁️ Cloudy"),
+    },
+    Entry {
+        code: WmoCode::Fog,
+        text: "Fog",
+        severity: Severity::Light,
+        color: (&L_GRAY, &L_GRAY),
+        nerd_font: "\u{e313} ~Foggy",
+        original: ("🌫️ Foggy", "🌫️ Foggy"),
+        technical: ("🌫️ Foggy", "🌫️ Foggy"),
+    },
+    Entry {
+        code: WmoCode::RimeFog,
+        text: "Depositing rime fog",
+        severity: Severity::Moderate,
+        color: (&L_GRAY, &L_GRAY),
+        nerd_font: "\u{e36f} Fog+Rime",
+        original: ("🌫️ Foggy", "🌫️ Foggy"),
+        technical: ("🌫️ Foggy", "🌫️ Foggy"),
+    },
+    Entry {
+        code: WmoCode::DrizzleLight,
+        text: "Light drizzle",
+        severity: Severity::Light,
+        color: (&CLEAR_BLUE, &CLEAR_BLUE),
+        nerd_font: "\u{e319} Drizzling-",
+        original: ("🌧️ Drizzle~", "🌧️ Drizzle~"),
+        technical: ("🌦️ Drizzle~", "🌧️ Drizzle~"),
+    },
+    Entry {
+        code: WmoCode::DrizzleModerate,
+        text: "Moderate drizzle",
+        severity: Severity::Light,
+        color: (&MID_BLUE, &MID_BLUE),
+        nerd_font: "\u{e319} Drizzling~",
+        original: ("🌧️ Drizzle", "🌧️ Drizzle"),
+        technical: ("🌧️ Drizzle", "🌧️ Drizzle"),
+    },
+    Entry {
+        code: WmoCode::DrizzleDense,
+        text: "Dense drizzle",
+        severity: Severity::Moderate,
+        color: (&DEEP_BLUE, &DEEP_BLUE),
+        nerd_font: "\u{e319} Drizzling+",
+        original: ("🌧️ Drizzle", "🌧️ Drizzle"),
+        technical: ("🌧️ Drizzle+", "🌧️ Drizzle+"),
+    },
+    Entry {
+        code: WmoCode::FreezingDrizzleLight,
+        text: "Light freezing drizzle",
+        severity: Severity::Heavy,
+        color: (&ICE_BLUE, &ICE_BLUE),
+        nerd_font: "\u{e3ad} Frz Drizzle-",
+        original: ("🧊 Icy Drizzle~", "🧊 Icy Drizzle~"),
+        technical: ("🧊 Frz Drizzle~", "🧊 Frz Drizzle~"),
+    },
+    Entry {
+        code: WmoCode::FreezingDrizzleDense,
+        text: "Dense freezing drizzle",
+        severity: Severity::Dangerous,
+        color: (&ICE_BLUE, &ICE_BLUE),
+        nerd_font: "\u{e3ad} Frz Drizzle+",
+        original: ("🧊 Icy Drizzle", "🧊 Icy Drizzle"),
+        technical: ("🧊 Frz Drizzle+", "🧊 Frz Drizzle+"),
+    },
+    Entry {
+        code: WmoCode::RainSlight,
+        text: "Slight rain",
+        severity: Severity::Light,
+        color: (&CLEAR_BLUE, &CLEAR_BLUE),
+        nerd_font: "\u{e318} Raining-",
+        original: ("🌧️ Rainy~", "🌧️ Rainy~"),
+        technical: ("🌦️ Rain~", "🌧️ Rain~"),
+    },
+    Entry {
+        code: WmoCode::RainModerate,
+        text: "Moderate rain",
+        severity: Severity::Moderate,
+        color: (&MID_BLUE, &MID_BLUE),
+        nerd_font: "\u{e318} Raining~",
+        original: ("🌧️ Rainy", "🌧️ Rainy"),
+        technical: ("🌧️ Rain", "🌧️ Rain"),
+    },
+    Entry {
+        code: WmoCode::RainHeavy,
+        text: "Heavy rain",
+        severity: Severity::Heavy,
+        color: (&DEEP_BLUE, &DEEP_BLUE),
+        nerd_font: "\u{e318} Raining+",
+        original: ("🌧️ Rain+", "🌧️ Rain+"),
+        technical: ("🌧️ Rain+", "🌧️ Rain+"),
+    },
+    Entry {
+        code: WmoCode::FreezingRainLight,
+        text: "Light freezing rain",
+        severity: Severity::Dangerous,
+        color: (&ICE_BLUE, &ICE_BLUE),
+        nerd_font: "\u{e3ad} Frz Rain-",
+        original: ("🧊 Icy Rain~", "🧊 Icy Rain~"),
+        technical: ("🧊 Frz Rain~", "🧊 Frz Rain~"),
+    },
+    Entry {
+        code: WmoCode::FreezingRainHeavy,
+        text: "Heavy freezing rain",
+        severity: Severity::Dangerous,
+        color: (&ICE_BLUE, &ICE_BLUE),
+        nerd_font: "\u{e3ad} Frz Rain+",
+        original: ("🧊 Icy Rain+", "🧊 Icy Rain+"),
+        technical: ("🧊 Frz Rain+", "🧊 Frz Rain+"),
+    },
+    Entry {
+        code: WmoCode::SnowSlight,
+        text: "Slight snow fall",
+        severity: Severity::Light,
+        color: (&CLEAR_BLUE, &CLEAR_BLUE),
+        nerd_font: "\u{e31a} Snowing-",
+        original: ("❄️ Snowy~", "❄️ Snowy~"),
+        technical: ("❄️ Snow~", "❄️ Snow~"),
+    },
+    Entry {
+        code: WmoCode::SnowModerate,
+        text: "Moderate snow fall",
+        severity: Severity::Moderate,
+        color: (&CLEAR_BLUE, &CLEAR_BLUE),
+        nerd_font: "\u{e31a} Snowing~",
+        original: ("❄️ Snowy", "❄️ Snowy"),
+        technical: ("❄️ Snow", "❄️ Snow"),
+    },
+    Entry {
+        code: WmoCode::SnowHeavy,
+        text: "Heavy snow fall",
+        severity: Severity::Heavy,
+        color: (&CLEAR_BLUE, &CLEAR_BLUE),
+        nerd_font: "\u{e31a} Snowing+",
+        original: ("❄️ Snowy", "❄️ Snowy"),
+        technical: ("❄️ Snow+", "❄️ Snow+"),
+    },
+    Entry {
+        code: WmoCode::SnowGrains,
+        text: "Snow grains",
+        severity: Severity::Light,
+        color: (&CLEAR_BLUE, &CLEAR_BLUE),
+        nerd_font: "\u{e31a} Snow Grains",
+        original: ("🌨️ Wintry", "🌨️ Wintry"),
+        technical: ("🌫️ Wintry", "🌫️ Wintry"),
+    },
+    Entry {
+        code: WmoCode::RainShowersSlight,
+        text: "Slight rain showers",
+        severity: Severity::Light,
+        color: (&CLEAR_BLUE, &CLEAR_BLUE),
+        nerd_font: "\u{e316} Showers-",
+        original: ("🌧️ Rainy~", "🌧️ Rainy~"),
+        technical: ("🌦️ Rainy~", "🌧️ Rainy~"),
+    },
+    Entry {
+        code: WmoCode::RainShowersModerate,
+        text: "Moderate rain showers",
+        severity: Severity::Moderate,
+        color: (&MID_BLUE, &MID_BLUE),
+        nerd_font: "\u{e316} Showers~",
+        original: ("🌧️ Rainy", "🌧️ Rainy"),
+        technical: ("🌧️ Rainy", "🌧️ Rainy"),
+    },
+    Entry {
+        code: WmoCode::RainShowersViolent,
+        text: "Violent rain showers",
+        severity: Severity::Heavy,
+        color: (&DEEP_BLUE, &DEEP_BLUE),
+        nerd_font: "\u{e316} Showers+",
+        original: ("🌧️ Rainy", "🌧️ Rainy"),
+        technical: ("🌧️ Rainy+", "🌧️ Rainy+"),
+    },
+    Entry {
+        code: WmoCode::SnowShowersSlight,
+        text: "Slight snow showers",
+        severity: Severity::Light,
+        color: (&CLEAR_BLUE, &CLEAR_BLUE),
+        nerd_font: "\u{e319} Snow Showers-",
+        original: ("❄️ Snowy~", "❄️ Snowy~"),
+        technical: ("❄️ Snowy~", "❄️ Snowy~"),
+    },
+    Entry {
+        code: WmoCode::SnowShowersHeavy,
+        text: "Heavy snow showers",
+        severity: Severity::Heavy,
+        color: (&CLEAR_BLUE, &CLEAR_BLUE),
+        nerd_font: "\u{e319} Snow Showers+",
+        original: ("❄️ Snowy", "❄️ Snowy"),
+        technical: ("❄️ Snowy", "❄️ Snowy"),
+    },
+    Entry {
+        code: WmoCode::Thunderstorm,
+        text: "Thunderstorm",
+        severity: Severity::Heavy,
+        color: (&YELLOW, &YELLOW),
+        nerd_font: "\u{e31d} Thunderstorm~",
+        original: ("⛈️ Thunderstorms", "⛈️ Thunderstorms"),
+        technical: ("⛈️ Thunderstorms", "⛈️ Thunderstorms"),
+    },
+    Entry {
+        code: WmoCode::ThunderstormSlightHail,
+        text: "Thunderstorm with slight hail",
+        severity: Severity::Dangerous,
+        color: (&YELLOW, &YELLOW),
+        nerd_font: "\u{e314} Thunder+Hail~",
+        original: ("⛈️ Hail Storms", "⛈️ Hail Storms"),
+        technical: ("⛈️ Hail Storm~", "⛈️ Hail Storm~"),
+    },
+    Entry {
+        code: WmoCode::ThunderstormHeavyHail,
+        text: "Thunderstorm with heavy hail",
+        severity: Severity::Dangerous,
+        color: (&YELLOW, &YELLOW),
+        nerd_font: "\u{e314} Thunder+Hail+",
+        original: ("⛈️ Hail Storms", "⛈️ Hail Storms"),
+        technical: ("⛈️ Hail Storm+", "⛈️ Hail Storm+"),
+    },
+];
+
+fn pick<T>((day_value, night_value): (T, T), day: bool) -> T {
+    if day {
+        day_value
+    } else {
+        night_value
+    }
+}
+
+impl WmoCode {
+    pub fn from_code(code: u8) -> Option<WmoCode> {
+        // 44 isn't documented but was seen in the wild, it means fog too
+        let code = if code == 44 { 45 } else { code };
+        TABLE.iter().find(|e| e.code as u8 == code).map(|e| e.code)
+    }
+
+    fn entry(self) -> &'static Entry {
+        TABLE
+            .iter()
+            .find(|e| e.code == self)
+            .expect("every WmoCode has a table entry")
+    }
+
+    pub fn text(self) -> &'static str {
+        self.entry().text
+    }
+
+    pub fn severity(self) -> Severity {
+        self.entry().severity
+    }
+
+    // the label and its color, nerd font glyphs don't change between day and night
+    // so neither does their color
+    pub fn label(self, emoji: &EmojiMode, day: bool) -> (&'static str, &'static Rgb) {
+        let e = self.entry();
+        match emoji {
+            EmojiMode::NerdFont => (e.nerd_font, e.color.1),
+            EmojiMode::Original => (pick(e.original, day), pick(e.color, day)),
+            EmojiMode::Technical => (pick(e.technical, day), pick(e.color, day)),
+        }
+    }
+}
+
+// columns the widest label of any emoji set takes, with the moon phase and its
+// illumination filled in and emoji followed by VS16 drawn wide
+pub fn label_width() -> usize {
+    TABLE
+        .iter()
+        .flat_map(|e| {
+            [
+                e.nerd_font,
+                e.original.0,
+                e.original.1,
+                e.technical.0,
+                e.technical.1,
+            ]
+        })
+        .map(|label| display_width(&label.replace("%m", "🌕").replace("%i", "100%"), Vs16::Wide))
+        .max()
+        .unwrap_or(0)
+}
+
+// plain description of a WMO code, used where icons and padding don't belong
+pub fn text(code: u8) -> &'static str {
+    WmoCode::from_code(code).map_or("Unknown", WmoCode::text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::width::{pad, Align};
+
+    #[test]
+    fn labels_fit_their_column() {
+        assert_eq!(label_width(), 16);
+        let (label, _) = WmoCode::Thunderstorm.label(&EmojiMode::Original, true);
+        assert_eq!(pad(label, label_width(), &Align::Left, Vs16::Wide), label);
+    }
+
+    #[test]
+    fn from_code() {
+        use WmoCode::*;
+        let cases = [
+            (56, FreezingDrizzleLight),
+            (57, FreezingDrizzleDense),
+            (66, FreezingRainLight),
+            (67, FreezingRainHeavy),
+            (96, ThunderstormSlightHail),
+            (99, ThunderstormHeavyHail),
+            (45, Fog),
+            // undocumented, seen in the wild
+            (44, Fog),
+        ];
+        for (code, wmo) in cases {
+            assert_eq!(WmoCode::from_code(code), Some(wmo), "{code}");
+        }
+        assert_eq!(text(57), "Dense freezing drizzle");
+        assert_eq!(text(99), "Thunderstorm with heavy hail");
+        // every entry reads back from its own code
+        for e in &TABLE {
+            assert_eq!(WmoCode::from_code(e.code as u8), Some(e.code));
+        }
+    }
+
+    #[test]
+    fn unknown_codes() {
+        for code in [4, 43, 46, 50, 58, 90, 97, 100, 255] {
+            assert_eq!(WmoCode::from_code(code), None, "{code}");
+        }
+        assert_eq!(text(100), "Unknown");
+    }
+
+    // the worst weather of a day is picked by severity, then by code
+    #[test]
+    fn severity_order() {
+        use Severity::*;
+        use WmoCode::*;
+        assert!(None < Light && Light < Moderate && Moderate < Heavy && Heavy < Dangerous);
+        assert_eq!(ClearSky.severity(), None);
+        assert_eq!(Overcast.severity(), None);
+        // ice and hail rank above heavy rain
+        assert!(FreezingDrizzleLight.severity() > DrizzleDense.severity());
+        assert!(FreezingRainLight.severity() > RainHeavy.severity());
+        assert_eq!(FreezingRainHeavy.severity(), Dangerous);
+        assert!(ThunderstormSlightHail.severity() > Thunderstorm.severity());
+        assert_eq!(ThunderstormHeavyHail.severity(), Dangerous);
+        assert!(RimeFog.severity() > Fog.severity());
+        // within a kind, heavier is at least as severe
+        for kind in [
+            [DrizzleLight, DrizzleModerate, DrizzleDense],
+            [RainSlight, RainModerate, RainHeavy],
+            [SnowSlight, SnowModerate, SnowHeavy],
+            [RainShowersSlight, RainShowersModerate, RainShowersViolent],
+        ] {
+            assert!(
+                kind.windows(2).all(|w| w[0].severity() <= w[1].severity()),
+                "{kind:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,25 @@
+#!/bin/sh
+# records the provider fixtures for Chicago with the requests weather-rs makes,
+# trimmed to two days. the tests pin their clock to the recorded days, update
+# DAY0 and NOW in the provider, render and bar tests after re-recording
+set -eu
+cd "$(dirname "$0")"
+
+lat=41.88
+lon=-87.63
+open_meteo="http://api.open-meteo.com/v1/forecast?latitude=$lat&longitude=$lon&current=temperature_2m,relative_humidity_2m,weather_code"
+daily="daily=temperature_2m_max,temperature_2m_min,sunrise,sunset,precipitation_probability_max,wind_speed_10m_max,weather_code,uv_index_max,uv_index_clear_sky_max"
+series="temperature_2m,relative_humidity_2m,dew_point_2m,apparent_temperature,precipitation,precipitation_probability,weather_code,wind_speed_10m,wind_direction_10m"
+rest="wind_speed_unit=mph&precipitation_unit=mm&timeformat=unixtime&timezone=America/Chicago&past_days=0&forecast_days=2"
+
+# every series, as the tables request it
+curl -sSf "$open_meteo&hourly=$series,pressure_msl&minutely_15=$series&$daily,temperature_2m_mean,relative_humidity_2m_max,relative_humidity_2m_min,relative_humidity_2m_mean,wind_speed_10m_min,wind_speed_10m_mean&$rest" \
+    >open_meteo.json
+# what the one line view requests
+curl -sSf "$open_meteo&minutely_15=$series&$daily&$rest" >open_meteo_current.json
+
+# api.met.no refuses requests without an identifying user agent
+curl -sSf -A "weather-rs github.com/chellipse/weather-rs" \
+    "https://api.met.no/weatherapi/locationforecast/2.0/complete?lat=$lat&lon=$lon" |
+    jq -c '.properties.timeseries |= ((.[0].time | fromdateiso8601) as $start
+        | map(select((.time | fromdateiso8601) <= $start + 172800)))' >met_norway.json